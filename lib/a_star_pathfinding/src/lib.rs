@@ -1,7 +1,9 @@
 use std::cmp::Reverse;
 use priority_queue::PriorityQueue;
-use std::f32::consts::SQRT_2;
 use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use pathfinding_core::{AgentTask, CostProfile, Graph, GridMap, Heuristic, MapfSolver, Octile, PathfindingError, Solution};
 
 pub type Agents = HashMap::<u32, Agent>;
 
@@ -27,16 +29,23 @@ impl Agent{
         }
     }
 
-    pub fn astar_search(&mut self, map: &AStarMap) -> Vec<(u32, u32)> {
-        AStarPathfinder::new().reconstruct_path(self.start_node, self.end_node, &map)
+    pub fn astar_search(&mut self, map: &AStarMap) -> Result<Vec<(u32, u32)>, PathfindingError> {
+        AStarPathfinder::new().reconstruct_path(self.start_node, self.end_node, map)
     }
 }
 
-#[derive(Default)]
 pub struct AStarMap {
-    pub data: Vec<u32>,
-    width: u32,
-    height: u32
+    pub grid: GridMap,
+    pub heuristic: Arc<dyn Heuristic>
+}
+
+impl Default for AStarMap {
+    fn default() -> Self {
+        AStarMap {
+            grid: GridMap::default(),
+            heuristic: Arc::new(Octile)
+        }
+    }
 }
 
 impl AStarMap {
@@ -46,72 +55,87 @@ impl AStarMap {
         height: u32
     ) -> AStarMap {
        AStarMap{
-            data,
-            width,
-            height,
+            grid: GridMap::new(data, width, height),
+            ..Default::default()
        }
     }
+}
 
-    //Get nodes neighbors (N,W,E,S)
-    fn get_neighbors(&self, position: (u32, u32)) -> Vec<(u32, u32)> {
+impl From<&GridMap> for AStarMap {
+    fn from(map: &GridMap) -> Self {
+        AStarMap {
+            grid: map.clone(),
+            ..Default::default()
+        }
+    }
+}
 
-        let mut neighbors =  Vec::<(u32, u32)>::new();
+//The grid answers every query through the profile, only the heuristic is the map's own
+impl Graph for AStarMap {
+    type Node = (u32, u32);
 
-        // NE
-        if position.0 < self.width - 1 && position.1 < self.height - 1{
-            neighbors.push((position.0 + 1, position.1 + 1));
-        }
+    fn get_node_count(&self) -> usize {
+        self.grid.data.len()
+    }
 
-        // SW
-        if position.0 > 0 && position.1 > 0 {
-            neighbors.push((position.0 - 1, position.1 - 1));
-        }
+    fn get_index(&self, position: (u32, u32)) -> usize {
+        self.grid.get_index(position)
+    }
 
-        // NW
-        if position.0 > 0 && position.1 < self.height - 1{
-            neighbors.push((position.0 - 1, position.1 + 1));
-        }
+    fn get_node(&self, index: usize) -> (u32, u32) {
+        self.grid.get_node(index)
+    }
 
-        // SE
-        if position.0 < self.width - 1 && position.1 > 0{
-            neighbors.push((position.0 + 1, position.1 - 1));
-        }
-        // E
-        if position.0 < self.width - 1 {
-            neighbors.push((position.0 + 1, position.1));
-        }
-        // W
-        if position.0 > 0 {
-            neighbors.push((position.0 - 1, position.1));
-        }
-        // N
-        if position.1 < self.height - 1 {
-            neighbors.push((position.0, position.1 + 1));
-        }
-        // W
-        if position.1 > 0 {
-            neighbors.push((position.0, position.1 - 1));
-        }
+    fn contains(&self, position: (u32, u32)) -> bool {
+        self.grid.contains(position)
+    }
 
-        neighbors
+    fn is_obstacle(&self, position: (u32, u32)) -> bool {
+        self.grid.is_obstacle_for(position, &CostProfile::default())
     }
 
-    // Get tuple position as an index for map lookup
-    fn get_index_position(&self, x: u32, y: u32) -> usize {
-        if y == 0 {
-            x as usize
-        } else if x == 0 {
-            (y * self.width as u32) as usize
-        } else {
-            ((y * self.width as u32) + x) as usize
-        }
+    fn get_neighbors(&self, position: (u32, u32)) -> Vec<((u32, u32), u32)> {
+        self.grid.get_neighbors_for(position, &CostProfile::default())
+    }
+
+    fn get_predecessors(&self, position: (u32, u32)) -> Vec<((u32, u32), u32)> {
+        self.grid.get_predecessors_for(position, &CostProfile::default())
+    }
+
+    fn get_wait_cost(&self, position: (u32, u32)) -> u32 {
+        self.grid.get_wait_cost_for(position, &CostProfile::default())
+    }
+
+    fn is_obstacle_for(&self, position: (u32, u32), profile: &CostProfile) -> bool {
+        self.grid.is_obstacle_for(position, profile)
+    }
+
+    fn get_neighbors_for(&self, position: (u32, u32), profile: &CostProfile) -> Vec<((u32, u32), u32)> {
+        self.grid.get_neighbors_for(position, profile)
+    }
+
+    fn get_predecessors_for(&self, position: (u32, u32), profile: &CostProfile) -> Vec<((u32, u32), u32)> {
+        self.grid.get_predecessors_for(position, profile)
+    }
+
+    fn get_wait_cost_for(&self, position: (u32, u32), profile: &CostProfile) -> u32 {
+        self.grid.get_wait_cost_for(position, profile)
+    }
+
+    fn heuristic(&self, from: (u32, u32), to: (u32, u32)) -> u32 {
+        self.heuristic.distance(from, to)
     }
 }
 
 #[derive(Default)]
 pub struct AStarPathfinder {
     pub path: Vec<(u32, u32)>,
-    prev: Vec<(u32, u32)>,
+    pub nodes_expanded: usize,
+
+    // Terrain the searching agent can cross and at what cost
+    pub profile: CostProfile,
+
+    prev: Vec<usize>,
     visited: Vec<bool>,
 }
 
@@ -130,62 +154,56 @@ impl AStarPathfinder {
         (tuple.0 as i32, tuple.1 as i32)
     }
 
+    pub fn reconstruct_path<G: Graph>(&mut self, start: G::Node, goal: G::Node, graph: &G) -> Result<Vec<G::Node>, PathfindingError<G::Node>> {
 
-    // We use Manhattan distance to calculate
-    // right angle distance between start and goal
-    fn heuristic(a: (i32, i32), b: (i32, i32)) -> usize {
-        (i32::abs(a.0 - b.0) + i32::abs(a.1 - b.1)) as usize
-    }
-
-    pub fn reconstruct_path(&mut self, start: (u32, u32), goal: (u32, u32), map: &AStarMap) -> Vec<(u32, u32)> {
+        self.search(start, goal, graph)?;
 
-        if map.data[map.get_index_position(goal.0, goal.1) as usize] < u32::MAX {
-            let mut path = Vec::<(u32, u32)>::new();
+        let mut path = Vec::<G::Node>::new();
+        let mut i = goal;
 
-            let _ = self.search(start, goal, map);
-            let mut i = goal;
+        path.push(i);
 
+        while i != start {
+            let i_index = graph.get_index(i);
+            i = graph.get_node(self.prev[i_index]);
             path.push(i);
-
-            while i != start {
-                let i_index = map.get_index_position(i.0, i.1) as usize;
-                path.push(self.prev[i_index]);
-                i = self.prev[i_index];
-            }
-
-            path
-        }else{
-            Vec::<(u32, u32)>::new()
         }
+
+        Ok(path)
     }
 
-    pub fn search(&mut self, start: (u32, u32), goal: (u32, u32), map: &AStarMap) -> u32 {
+    pub fn search<G: Graph>(&mut self, start: G::Node, goal: G::Node, graph: &G) -> Result<usize, PathfindingError<G::Node>> {
 
-        let start_index = map.get_index_position(start.0 , start.1);
+        for node in [start, goal].iter() {
+            if !graph.contains(*node) {
+                return Err(PathfindingError::OutOfBounds(*node));
+            }
+        }
 
-        // if goal position is equal to u32::MAX, the goal is unreachable
-        if map.data[start_index] == u32::MAX {
-            return u32::MAX;
+        // if start is an obstacle, the agent can't move
+        if graph.is_obstacle_for(start, &self.profile) {
+            return Err(PathfindingError::StartBlocked(start));
         }
 
+        // if goal is an obstacle, the goal is unreachable
+        if graph.is_obstacle_for(goal, &self.profile) {
+            return Err(PathfindingError::GoalUnreachable { start, goal });
+        }
+
+        let start_index = graph.get_index(start);
+
         // This priority queue will be ordered by the reverse of the highest cost
-        // so, the priority of nodes exploration will depend of their accessibility cost
-        let mut open_list: PriorityQueue<(u32, u32), Reverse<usize>> = PriorityQueue::new();
+        // so, the priority of nodes exploration will depend of their estimated total cost
+        let mut open_list: PriorityQueue<G::Node, Reverse<u32>> = PriorityQueue::new();
 
         //Represent the nodes which have already been visited
-        self.visited = std::iter::repeat(false)
-            .take(map.data.len())
-            .collect::<Vec<bool>>();
+        self.visited = vec![false; graph.get_node_count()];
 
-        //This Array contains the costs to visiting each tile,
+        //This Array contains the costs to visiting each node,
         //they are initialy set to 'infinity'
-        let mut cost_so_far = std::iter::repeat(usize::MAX)
-            .take(map.data.len())
-            .collect::<Vec<usize>>();
+        let mut cost_so_far = vec![u32::MAX; graph.get_node_count()];
 
-        self.prev = std::iter::repeat((0, 0))
-            .take(map.data.len())
-            .collect::<Vec<(u32, u32)>>();
+        self.prev = vec![start_index; graph.get_node_count()];
 
         self.visited[start_index] = true;
         cost_so_far[start_index] = 0;
@@ -193,55 +211,95 @@ impl AStarPathfinder {
 
         while let Some((current, Reverse(_current_cost))) = open_list.pop() {
 
-            let current_pos = map.get_index_position(current.0, current.1);
+            let current_index = graph.get_index(current);
+            self.nodes_expanded += 1;
 
             // Once we find the correct node,
-            // return current pos
+            // return its index
             if current == goal {
-                return current_pos as u32;
+                return Ok(current_index);
             }
 
-            for next in map.get_neighbors(current) {
-
-                let next_pos = map.get_index_position(next.0, next.1);
+            // Obstacles are already left out of the neighbors
+            for (next, move_cost) in graph.get_neighbors_for(current, &self.profile) {
 
-                let mut new_cost = 0;
+                let next_index = graph.get_index(next);
 
                 // We take the current node cost incremented
-                // from the cost of next node
-                // If diagonal, add an extra cost for traversing
-                if current.0 != next.0 && current.1 != next.1 {
-                    new_cost = cost_so_far[current_pos] + (map.data[next_pos] as f32 * SQRT_2) as usize;
-                }else{
-                    new_cost = cost_so_far[current_pos] + map.data[next_pos] as usize;
-                }
+                // from the cost of the move
+                let new_cost = cost_so_far[current_index].saturating_add(move_cost);
 
                 // Node duplication detection
-                if !self.visited[next_pos] || new_cost < cost_so_far[next_pos] {
+                if !self.visited[next_index] || new_cost < cost_so_far[next_index] {
 
-                    self.visited[next_pos] = true;
+                    self.visited[next_index] = true;
 
                     // If this new cost is lesser than actual one,
-                    // update cost map with this cost + heuristic cost (Manhattan distance)
-                    let heuristic = AStarPathfinder::heuristic(
-                        AStarPathfinder::tuple_u32_as_i32(goal),
-                        AStarPathfinder::tuple_u32_as_i32(next));
-                    cost_so_far[next_pos] = new_cost + heuristic;
-                    self.prev[next_pos] = current;
-
-                    //Update priority queue with this new cost
-                    open_list.push_decrease(next, Reverse(new_cost));
-                    /* match open_list.get(&next) {
-                        Some((item, priority)) => {
-                            open_list.push_decrease(*item, Reverse(new_cost));
-                        },
-                        None => {
-                            open_list.push(next, Reverse(new_cost));
-                        }
-                    }*/
+                    // update cost map with it, the node is explored by its cost + heuristic cost
+                    cost_so_far[next_index] = new_cost;
+                    self.prev[next_index] = current_index;
+
+                    //Update priority queue with this new estimation
+                    let estimation = new_cost.saturating_add(graph.heuristic(next, goal));
+                    open_list.push_increase(next, Reverse(estimation));
                 }
             }
         }
-        u32::MAX
+        Err(PathfindingError::GoalUnreachable { start, goal })
+    }
+}
+
+// Plans every agent on its own with A*, ignoring the other agents
+pub struct AStarSolver {
+    pub heuristic: Arc<dyn Heuristic>
+}
+
+impl Default for AStarSolver {
+    fn default() -> Self {
+        AStarSolver::new()
+    }
+}
+
+impl AStarSolver {
+    pub fn new() -> AStarSolver {
+        AStarSolver {
+            heuristic: Arc::new(Octile)
+        }
     }
-}
\ No newline at end of file
+}
+
+impl MapfSolver for AStarSolver {
+
+    fn name(&self) -> &str {
+        "astar"
+    }
+
+    fn solve(&mut self, map: &GridMap, tasks: &[AgentTask]) -> Result<Solution, PathfindingError> {
+
+        let start_time = Instant::now();
+        let mut a_star_map = AStarMap::from(map);
+        a_star_map.heuristic = self.heuristic.clone();
+        let mut solution = Solution::default();
+
+        for task in tasks {
+            map.check_task(task)?;
+
+            let mut pathfinder = AStarPathfinder {
+                profile: task.profile,
+                ..AStarPathfinder::new()
+            };
+
+            // The path is rebuilt from the goal, so it is reversed to start from the agent
+            let mut path = pathfinder.reconstruct_path(task.start, task.goal, &a_star_map)?;
+            path.reverse();
+
+            solution.stats.nodes_expanded += pathfinder.nodes_expanded;
+            solution.stats.nodes_expanded_per_agent.push(pathfinder.nodes_expanded);
+            solution.paths.push(path);
+        }
+
+        solution.stats.planning_time = start_time.elapsed();
+        solution.stats.tick_times = vec![solution.stats.planning_time];
+        Ok(solution)
+    }
+}
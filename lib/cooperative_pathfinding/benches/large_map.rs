@@ -0,0 +1,133 @@
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use a_star_pathfinding::AStarSolver;
+use cooperative_pathfinding::{DistanceStore, PagedSearchState, ReverseResumableAStar, SearchState, WhcaSolver, WorldMap};
+use pathfinding_core::{AgentTask, Graph, GridMap, MapfSolver};
+
+#[path = "../../pathfinding_core/tests/common/mod.rs"]
+mod common;
+use common::Lcg;
+
+/* Compares the planners on a large map scattered with obstacles, run it with `cargo bench`.
+The map side and the agent count can be given after `--`, e.g. `cargo bench -- 512 200` */
+
+const DEFAULT_SIZE: u32 = 256;
+const DEFAULT_AGENTS: usize = 100;
+const RUNS: u32 = 5;
+
+// Bytes allocated right now, and the most allocated at once since the last reset of the peak
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+/* Allocator of the benchmark, keeping track of the peak memory of each run */
+struct PeakAllocator;
+
+unsafe impl GlobalAlloc for PeakAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(allocated, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: PeakAllocator = PeakAllocator;
+
+fn get_scenario(size: u32, agents: usize) -> (GridMap, Vec<AgentTask>) {
+    // The same seed always gives the same map and tasks
+    let mut random = Lcg::new(42);
+
+    // One tile out of ten is an obstacle
+    let data = (0..size * size)
+        .map(|_| if random.next_u32().is_multiple_of(10) { u32::MAX } else { 1 })
+        .collect::<Vec<u32>>();
+    let map = GridMap::new(data, size, size);
+
+    // Starts and goals are all different
+    let mut used = HashSet::new();
+    let mut pick = || loop {
+        let pos = (random.next_u32() % size, random.next_u32() % size);
+        if !map.is_obstacle(pos) && used.insert(pos) {
+            return pos;
+        }
+    };
+
+    let tasks = (0..agents)
+        .map(|id| AgentTask::new(id as u32, pick(), pick()))
+        .collect();
+
+    (map, tasks)
+}
+
+/* Will run the planning RUNS times, printing the mean time, the nodes expanded by the last run
+and the most memory a run allocated on top of what was allocated before it */
+fn bench<F: FnMut() -> usize>(name: &str, mut plan: F) {
+    let mut total = Duration::default();
+    let mut nodes_expanded = 0;
+    let mut peak = 0;
+
+    for _ in 0..RUNS {
+        let allocated = ALLOCATED.load(Ordering::Relaxed);
+        PEAK.store(allocated, Ordering::Relaxed);
+
+        let start = Instant::now();
+        nodes_expanded = plan();
+        total += start.elapsed();
+
+        peak = peak.max(PEAK.load(Ordering::Relaxed) - allocated);
+    }
+
+    println!("{:<24} {:>12.3?} {:>12} nodes {:>10.1} MB", name, total / RUNS, nodes_expanded, peak as f64 / 1e6);
+}
+
+/* Will search the distance from each start to its goal, returning the nodes expanded */
+fn plan_heuristics<S: DistanceStore, F: Fn() -> S>(map: &WorldMap, tasks: &[AgentTask], new_state: F) -> usize {
+    let heuristics = tasks.iter()
+        .map(|task| {
+            let mut heuristic = ReverseResumableAStar::with_state(task.goal, task.start, task.profile, new_state());
+            heuristic.get_distance(map, task.start).unwrap();
+            heuristic
+        })
+        .collect::<Vec<ReverseResumableAStar<(u32, u32), S>>>();
+
+    heuristics.iter().map(|heuristic| heuristic.nodes_expanded).sum()
+}
+
+fn main() {
+    let mut args = std::env::args().skip(1).filter(|arg| !arg.starts_with('-'));
+    let size = args.next().and_then(|arg| arg.parse().ok()).unwrap_or(DEFAULT_SIZE);
+    let agents = args.next().and_then(|arg| arg.parse().ok()).unwrap_or(DEFAULT_AGENTS);
+
+    let (map, tasks) = get_scenario(size, agents);
+    let world_map = WorldMap::from(&map);
+
+    println!("{}x{} map, {} agents", size, size, agents);
+
+    // Single agent searches from each start to its goal, ignoring the other agents
+    bench("astar", || AStarSolver::new().solve(&map, &tasks).unwrap().stats.nodes_expanded);
+
+    // The heuristic of every goal, all kept alive as during cooperative planning. The dense
+    // store holds a slot per tile for each goal, the paged one only the pages each search reached
+    bench("rra*, dense", || plan_heuristics(&world_map, &tasks, SearchState::new));
+    bench("rra*, paged", || plan_heuristics(&world_map, &tasks, PagedSearchState::new));
+
+    // Cooperative planning of every agent, the heuristics on one thread then on every core.
+    // Only the heuristics run in parallel, the space-time searches stay sequential, so the
+    // second run can only be faster with several cores and the gain depends on the machine
+    for threads in [1, 0].iter() {
+        let mut solver = WhcaSolver::new();
+        solver.threads = *threads;
+
+        bench(&format!("whca, {} threads", threads), || solver.solve(&map, &tasks).unwrap().stats.nodes_expanded);
+    }
+}
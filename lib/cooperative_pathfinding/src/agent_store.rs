@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::ops::RangeInclusive;
+use rayon::prelude::*;
+use pathfinding_core::{CostProfile, Graph};
+
+use crate::{Agent, Node, PlannerConfig, ReverseResumableAStar, SearchState};
+
+/* Agents of a WHCA* run indexed by their id, ids are given from 1 in the order
+the agents are added. It owns the agents, so planning borrows one of them at a time,
+along with the Reverse Resumable A* of its goal, shared by the agents heading there
+with the same cost profile */
+#[derive(Debug)]
+pub struct AgentStore<P: Eq + Hash = (u32, u32)> {
+    agents: Vec<Agent<P>>,
+    heuristics: HashMap<(P, CostProfile), ReverseResumableAStar<P>>,
+
+    // Reused by the space-time search of every agent, they are planned one at a time
+    search_state: SearchState
+}
+
+impl<P: Copy + Eq + Hash + Debug> Default for AgentStore<P> {
+    fn default() -> Self {
+        AgentStore::new()
+    }
+}
+
+impl<P: Copy + Eq + Hash + Debug> AgentStore<P> {
+
+    pub fn new() -> AgentStore<P> {
+        AgentStore {
+            agents: Vec::new(),
+            heuristics: HashMap::new(),
+            search_state: SearchState::new()
+        }
+    }
+
+    /* Will create an agent crossing the terrain with the given profile and return its id */
+    pub fn add(&mut self, name: &str, start: Node<P>, goal: Node<P>, profile: CostProfile, config: &PlannerConfig) -> u32 {
+        let id = self.agents.len() as u32 + 1;
+        self.heuristics.entry((goal.pos, profile)).or_insert_with(|| ReverseResumableAStar::new(goal.pos, start.pos, profile));
+        self.agents.push(Agent::new(id, name, start, goal, profile, config));
+        id
+    }
+
+    pub fn len(&self) -> usize {
+        self.agents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.agents.is_empty()
+    }
+
+    pub fn get_ids(&self) -> RangeInclusive<u32> {
+        1..=self.agents.len() as u32
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Agent<P>> {
+        self.agents.get((id as usize).checked_sub(1)?)
+    }
+
+    pub fn get_mut(&mut self, id: u32) -> Option<&mut Agent<P>> {
+        self.agents.get_mut((id as usize).checked_sub(1)?)
+    }
+
+    /* Agents by increasing ids */
+    pub fn iter(&self) -> std::slice::Iter<'_, Agent<P>> {
+        self.agents.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, Agent<P>> {
+        self.agents.iter_mut()
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [Agent<P>] {
+        &mut self.agents
+    }
+
+    pub fn get_heuristic(&self, goal: P, profile: CostProfile) -> Option<&ReverseResumableAStar<P>> {
+        self.heuristics.get(&(goal, profile))
+    }
+
+    /* Will borrow an agent along with what planning it needs, the heuristic of its goal,
+    created for the agent if its goal or its profile was changed since it was added, and the search state */
+    pub fn get_mut_for_planning(&mut self, id: u32) -> Option<(&mut Agent<P>, &mut ReverseResumableAStar<P>, &mut SearchState)> {
+        let agent = self.agents.get_mut((id as usize).checked_sub(1)?)?;
+        let (goal, profile) = (agent.goal.pos, agent.profile);
+        let start = agent.current_node.pos;
+        let heuristic = self.heuristics.entry((goal, profile)).or_insert_with(|| ReverseResumableAStar::new(goal, start, profile));
+
+        Some((agent, heuristic, &mut self.search_state))
+    }
+
+    /* Will resume the heuristics over the window of each agent, until the neighbors of every node
+    along its best route over the next window are expanded, the nodes its next space-time search reads
+    when nothing is in its way. The heuristics of different goals or profiles run in parallel
+    on the current rayon thread pool, each one is resumed for its agents in the order of the ids given */
+    pub fn expand_heuristics<G: Graph<Node = P> + Sync>(&mut self, graph: &G, ids: &[u32])
+    where P: Send + Sync {
+
+        let mut queries = HashMap::<(P, CostProfile), Vec<(u32, P, u32)>>::new();
+
+        for agent in ids.iter().filter_map(|id| self.get(*id)) {
+            queries.entry((agent.goal.pos, agent.profile)).or_default().push((agent.id, agent.current_node.pos, agent.window_size));
+        }
+
+        for ((goal, profile), positions) in queries.iter() {
+            self.heuristics.entry((*goal, *profile)).or_insert_with(|| ReverseResumableAStar::new(*goal, positions[0].1, *profile));
+        }
+
+        // The searches only read the graph, the nodes they expand are counted for the agents asking
+        let nodes_expanded = self.heuristics.par_iter_mut()
+            .filter_map(|(key, heuristic)| Some((heuristic, queries.get(key)?)))
+            .flat_map_iter(|(heuristic, positions)| {
+                positions.iter()
+                    .map(|(id, pos, window_size)| {
+                        let nodes_expanded = heuristic.nodes_expanded;
+                        expand_route(graph, heuristic, *pos, *window_size);
+                        (*id, heuristic.nodes_expanded - nodes_expanded)
+                    })
+                    .collect::<Vec<(u32, usize)>>()
+            })
+            .collect::<Vec<(u32, usize)>>();
+
+        for (id, count) in nodes_expanded {
+            self.get_mut(id).unwrap().nodes_expanded += count;
+        }
+    }
+}
+
+/* Will follow the best moves from pos toward the goal of the heuristic for the given number of moves,
+the other agents ignored, resuming the search until the neighbors of every node on the way are expanded */
+fn expand_route<G: Graph>(graph: &G, heuristic: &mut ReverseResumableAStar<G::Node>, pos: G::Node, moves: u32) {
+
+    let profile = heuristic.get_profile();
+    let mut pos = pos;
+
+    if heuristic.get_distance(graph, pos).is_none() {
+        return;
+    }
+
+    for _ in 0..moves {
+        if pos == heuristic.get_goal() {
+            return;
+        }
+
+        let mut best = None;
+        let mut best_cost = u32::MAX;
+
+        for (next, move_cost) in graph.get_neighbors_for(pos, &profile) {
+            let cost = heuristic.get_distance(graph, next).map_or(u32::MAX, |distance| move_cost.saturating_add(distance));
+
+            if cost < best_cost {
+                best = Some(next);
+                best_cost = cost;
+            }
+        }
+
+        match best {
+            Some(next) => pos = next,
+            None => return
+        }
+    }
+}
@@ -0,0 +1,348 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+use priority_queue::priority_queue::PriorityQueue;
+use pathfinding_core::{AgentTask, CostProfile, DiagonalCrossing, Graph, GridMap, MapfSolver, PathfindingError, Solution, TimedPath};
+
+use crate::{Node, ReverseResumableAStar, WorldMap};
+
+type Position = (u32, u32);
+
+const DEFAULT_MAX_EXPANSIONS: usize = 10_000;
+
+/* A constraint forbids an agent to be on a tile, or to traverse an edge,
+when arriving at the timestep `time` */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Constraint {
+    Vertex { agent: usize, pos: Position, time: u32 },
+    Edge { agent: usize, from: Position, to: Position, time: u32 }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Conflict {
+    Vertex { a: usize, b: usize, pos: Position, time: u32 },
+    Edge { a: usize, a_move: (Position, Position), b: usize, b_move: (Position, Position), time: u32 }
+}
+
+/* Node of the constraint tree */
+#[derive(Debug, Clone, Default)]
+struct ConstraintNode {
+    constraints: Vec<Constraint>,
+    paths: Vec<TimedPath>,
+    costs: Vec<u32>,
+    cost: u32
+}
+
+/* Conflict-Based Search, finds a set of timed paths minimizing the sum of costs
+by splitting every conflict into two constrained subproblems */
+#[derive(Debug, Clone)]
+pub struct ConflictBasedSearch {
+    pub diagonal_crossing: DiagonalCrossing,
+
+    // Bound on the constraint tree nodes expanded before giving up
+    pub max_expansions: usize,
+
+    // Nodes expanded by the low level searches
+    pub nodes_expanded: usize,
+
+    // Nodes expanded by the low level searches of each agent during the last search
+    pub nodes_expanded_per_agent: Vec<usize>,
+
+    // Reverse Resumable A* of each goal and cost profile, giving the true distances used as the low level heuristic
+    heuristics: HashMap<(Position, CostProfile), ReverseResumableAStar>
+}
+
+impl Default for ConflictBasedSearch {
+    fn default() -> Self {
+        ConflictBasedSearch::new()
+    }
+}
+
+impl ConflictBasedSearch {
+
+    pub fn new() -> ConflictBasedSearch {
+        ConflictBasedSearch {
+            diagonal_crossing: DiagonalCrossing::default(),
+            max_expansions: DEFAULT_MAX_EXPANSIONS,
+            nodes_expanded: 0,
+            nodes_expanded_per_agent: Vec::new(),
+            heuristics: HashMap::new()
+        }
+    }
+
+    /* Will return one conflict-free timed path per (start, goal, profile) agent */
+    pub fn search(&mut self, map: &WorldMap, agents: &[(Node, Node, CostProfile)]) -> Result<Vec<TimedPath>, PathfindingError> {
+
+        self.heuristics.clear();
+        for (start, goal, profile) in agents.iter() {
+            self.heuristics.entry((goal.pos, *profile)).or_insert_with(|| ReverseResumableAStar::new(goal.pos, start.pos, *profile));
+        }
+        self.nodes_expanded_per_agent = vec![0; agents.len()];
+
+        let mut root = ConstraintNode::default();
+
+        for (agent, (start, goal, profile)) in agents.iter().enumerate() {
+
+            for node in [start, goal].iter() {
+                if !map.contains(node.pos) {
+                    return Err(PathfindingError::OutOfBounds(node.pos));
+                }
+            }
+
+            if map.is_obstacle_for(start.pos, profile) {
+                return Err(PathfindingError::StartBlocked(start.pos));
+            }
+
+            let (path, cost) = self.low_level_search(map, agent, start.pos, goal.pos, profile, &[])
+                .ok_or(PathfindingError::GoalUnreachable { start: start.pos, goal: goal.pos })?;
+            root.paths.push(path);
+            root.costs.push(cost);
+        }
+        root.cost = root.costs.iter().fold(0, |sum, cost| sum.saturating_add(*cost));
+
+        // The tree nodes are ordered by the lowest sum of costs,
+        // then by creation order to keep the search deterministic
+        let mut tree = vec![root];
+        let mut open_set = PriorityQueue::<usize, Reverse<(u32, usize)>>::new();
+        open_set.push(0, Reverse((tree[0].cost, 0)));
+
+        let mut expansions = 0;
+
+        while let Some((current, _)) = open_set.pop() {
+
+            let node = std::mem::take(&mut tree[current]);
+
+            let conflict = match self.find_first_conflict(map, &node.paths) {
+                None => return Ok(node.paths),
+                Some(conflict) => conflict
+            };
+
+            expansions += 1;
+            if expansions > self.max_expansions {
+                return Err(PathfindingError::ExpansionLimitReached(self.max_expansions));
+            }
+
+            let branches = match conflict {
+                Conflict::Vertex { a, b, pos, time } => [
+                    Constraint::Vertex { agent: a, pos, time },
+                    Constraint::Vertex { agent: b, pos, time }
+                ],
+                Conflict::Edge { a, a_move, b, b_move, time } => [
+                    Constraint::Edge { agent: a, from: a_move.0, to: a_move.1, time },
+                    Constraint::Edge { agent: b, from: b_move.0, to: b_move.1, time }
+                ]
+            };
+
+            for constraint in branches.iter() {
+
+                let agent = match constraint {
+                    Constraint::Vertex { agent, .. } => *agent,
+                    Constraint::Edge { agent, .. } => *agent
+                };
+
+                let mut child = ConstraintNode {
+                    constraints: node.constraints.clone(),
+                    paths: node.paths.clone(),
+                    costs: node.costs.clone(),
+                    cost: 0
+                };
+                child.constraints.push(*constraint);
+
+                let (start, goal, profile) = agents[agent];
+
+                // This branch has no solution, it is pruned
+                let (path, cost) = match self.low_level_search(map, agent, start.pos, goal.pos, &profile, &child.constraints) {
+                    None => continue,
+                    Some(result) => result
+                };
+
+                child.paths[agent] = path;
+                child.costs[agent] = cost;
+                child.cost = child.costs.iter().fold(0, |sum, cost| sum.saturating_add(*cost));
+
+                let index = tree.len();
+                open_set.push(index, Reverse((child.cost, index)));
+                tree.push(child);
+            }
+        }
+
+        Err(PathfindingError::NoSolution)
+    }
+
+    /* Space-time A* of one agent crossing the terrain with its profile, respecting its constraints,
+    returns its timed path and its cost */
+    fn low_level_search(&mut self, map: &WorldMap, agent: usize, start: Position, goal: Position, profile: &CostProfile, constraints: &[Constraint]) -> Option<(TimedPath, u32)> {
+
+        let heuristic = self.heuristics.get_mut(&(goal, *profile))?;
+        let mut nodes_expanded = 0;
+
+        let start_h = heuristic.get_distance(map, start)?;
+
+        let mut vertex_constraints = HashSet::<(Position, u32)>::new();
+        let mut edge_constraints = HashSet::<(Position, Position, u32)>::new();
+
+        // The agent may only stop on its goal once no constraint can force it away
+        let mut last_goal_constraint = 0;
+        let mut last_constraint = 0;
+
+        for constraint in constraints.iter() {
+            match *constraint {
+                Constraint::Vertex { agent: constrained, pos, time } if constrained == agent => {
+                    vertex_constraints.insert((pos, time));
+                    last_constraint = last_constraint.max(time);
+                    if pos == goal {
+                        last_goal_constraint = last_goal_constraint.max(time);
+                    }
+                }
+                Constraint::Edge { agent: constrained, from, to, time } if constrained == agent => {
+                    edge_constraints.insert((from, to, time));
+                    last_constraint = last_constraint.max(time);
+                }
+                _ => {}
+            }
+        }
+
+        // Past the last constraint, states only differ by their position,
+        // so the time is clamped to keep the state space finite
+        let state_key = |pos: Position, time: u32| (pos, time.min(last_constraint + 1));
+
+        let mut open_set = PriorityQueue::<(Position, u32), Reverse<(u32, Reverse<u32>)>>::new();
+        let mut cost_so_far = HashMap::<(Position, u32), u32>::new();
+        let mut came_from = HashMap::<(Position, u32), (Position, u32)>::new();
+        let mut closed_set = HashSet::<(Position, u32)>::new();
+
+        cost_so_far.insert((start, 0), 0);
+        open_set.push((start, 0), Reverse((start_h, Reverse(0))));
+
+        while let Some(((pos, time), _)) = open_set.pop() {
+
+            let current_cost = cost_so_far[&(pos, time)];
+            nodes_expanded += 1;
+
+            if pos == goal && time >= last_goal_constraint {
+                let mut path = vec![pos];
+                let mut state = (pos, time);
+
+                while let Some(previous) = came_from.get(&state) {
+                    path.push(previous.0);
+                    state = *previous;
+                }
+                path.reverse();
+
+                self.nodes_expanded += nodes_expanded;
+                self.nodes_expanded_per_agent[agent] += nodes_expanded;
+                return Some((path, current_cost));
+            }
+
+            if !closed_set.insert(state_key(pos, time)) {
+                continue;
+            }
+
+            for (_, next) in map.grid.get_successors(pos, profile) {
+
+                let next_time = time + 1;
+
+                if vertex_constraints.contains(&(next, next_time))
+                    || edge_constraints.contains(&(pos, next, next_time)) {
+                    continue;
+                }
+
+                let h = match heuristic.get_distance(map, next) {
+                    None => continue,
+                    Some(distance) => distance
+                };
+
+                if closed_set.contains(&state_key(next, next_time)) {
+                    continue;
+                }
+
+                let new_cost = current_cost.saturating_add(map.grid.get_move_cost(pos, next, profile));
+
+                if new_cost < *cost_so_far.get(&(next, next_time)).unwrap_or(&u32::MAX) {
+                    cost_so_far.insert((next, next_time), new_cost);
+                    came_from.insert((next, next_time), (pos, time));
+
+                    // Ties are broken in favor of the deepest states
+                    open_set.push_increase((next, next_time), Reverse((new_cost.saturating_add(h), Reverse(next_time))));
+                }
+            }
+        }
+
+        self.nodes_expanded += nodes_expanded;
+        self.nodes_expanded_per_agent[agent] += nodes_expanded;
+        None
+    }
+
+    fn find_first_conflict(&self, map: &WorldMap, paths: &[TimedPath]) -> Option<Conflict> {
+
+        let makespan = paths.iter().map(|path| path.len()).max().unwrap_or(0) as u32;
+        let position = |agent: usize, time: u32| {
+            let path = &paths[agent];
+            path[(time as usize).min(path.len() - 1)]
+        };
+
+        for time in 0..makespan {
+            for a in 0..paths.len() {
+                for b in a + 1..paths.len() {
+
+                    let a_pos = position(a, time);
+                    let b_pos = position(b, time);
+
+                    if a_pos == b_pos {
+                        return Some(Conflict::Vertex { a, b, pos: a_pos, time });
+                    }
+
+                    if time == 0 {
+                        continue;
+                    }
+
+                    let a_move = (position(a, time - 1), a_pos);
+                    let b_move = (position(b, time - 1), b_pos);
+
+                    if map.is_edge_conflict(a_move, b_move, self.diagonal_crossing) {
+                        return Some(Conflict::Edge { a, a_move, b, b_move, time });
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+impl MapfSolver for ConflictBasedSearch {
+
+    fn name(&self) -> &str {
+        "cbs"
+    }
+
+    fn solve(&mut self, map: &GridMap, tasks: &[AgentTask]) -> Result<Solution, PathfindingError> {
+
+        let start_time = Instant::now();
+        for task in tasks {
+            map.check_task(task)?;
+        }
+        AgentTask::check_goals(tasks)?;
+
+        let world_map = WorldMap::from(map);
+
+        let agents = tasks.iter()
+            .map(|task| (Node::from((task.start.0, task.start.1, 0)), Node::from((task.goal.0, task.goal.1, 0)), task.profile))
+            .collect::<Vec<(Node, Node, CostProfile)>>();
+
+        self.nodes_expanded = 0;
+
+        let paths = self.search(&world_map, &agents)?;
+
+        let mut solution = Solution {
+            paths,
+            diagonal_crossing: self.diagonal_crossing,
+            ..Default::default()
+        };
+        solution.stats.nodes_expanded = self.nodes_expanded;
+        solution.stats.nodes_expanded_per_agent = self.nodes_expanded_per_agent.clone();
+        solution.stats.planning_time = start_time.elapsed();
+        solution.stats.tick_times = vec![solution.stats.planning_time];
+        Ok(solution)
+    }
+}
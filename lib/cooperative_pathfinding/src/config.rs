@@ -0,0 +1,132 @@
+use pathfinding_core::DiagonalCrossing;
+
+use crate::{PriorityStrategy, REPLAN_INTERVAL, WINDOW_SIZE};
+
+// Windows without progress before an agent is given the first pick of the reservations
+const DEFAULT_STALL_WINDOWS: u32 = 3;
+
+// Steps in a row agents wait on each other before their cycle counts as a deadlock
+const DEFAULT_DEADLOCK_WAITS: u32 = 2;
+
+/* Order of the space-time states sharing the same f score */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TieBreaking {
+    // Left to the priority queue
+    #[default]
+    None,
+
+    // The state with the highest g score, the deepest in the window, comes first
+    HighestG,
+
+    // The state with the lowest g score comes first
+    LowestG
+}
+
+impl TieBreaking {
+
+    /* Secondary priority of a state, the lowest is expanded first */
+    pub fn get_priority(&self, g_score: u32) -> u32 {
+        match self {
+            TieBreaking::None => 0,
+            TieBreaking::HighestG => u32::MAX - g_score,
+            TieBreaking::LowestG => g_score
+        }
+    }
+}
+
+/* What an agent does once it reaches its goal, parked agents
+only act on the others through their reservations */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GoalParking {
+    // Rests on its goal and keeps it reserved for good, past the end of its windows
+    #[default]
+    Stay,
+
+    // Leaves the map on arrival, releasing its reservations
+    Vanish,
+
+    // Rests on its goal but lets the others plan through it,
+    // stepping aside when one of them takes the tile and coming back after
+    Yield
+}
+
+/* Settings of the Windowed Hierarchical Cooperative A*, given to the agents when they are created */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlannerConfig {
+    // Steps planned ahead by each space-time search
+    pub window_size: u32,
+
+    // Steps walked between two replannings, a window never gets shorter than this
+    pub replan_interval: u32,
+
+    // Agents replan at different steps, so the planning cost is spread over the interval
+    // and no agent always gets the first pick of the reservations
+    pub staggered_replanning: bool,
+
+    pub priority: PriorityStrategy,
+
+    // An agent replanning this many windows in a row without getting closer to its goal
+    // has the agents in its way replan after it, 0 never reorders the agents
+    pub stall_windows: u32,
+
+    // Agents each waiting this many steps in a row for the tile of the next one in a cycle
+    // are in a deadlock and replan together, 0 never looks for deadlocks
+    pub deadlock_waits: u32,
+
+    pub tie_breaking: TieBreaking,
+
+    pub goal_parking: GoalParking,
+
+    // Whether the reservations let two agents cross on the diagonals of the same square
+    pub diagonal_crossing: DiagonalCrossing
+}
+
+impl Default for PlannerConfig {
+    fn default() -> Self {
+        PlannerConfig::new(WINDOW_SIZE, REPLAN_INTERVAL)
+    }
+}
+
+impl PlannerConfig {
+
+    pub fn new(window_size: u32, replan_interval: u32) -> PlannerConfig {
+        PlannerConfig {
+            window_size,
+            replan_interval,
+            staggered_replanning: true,
+            priority: PriorityStrategy::default(),
+            stall_windows: DEFAULT_STALL_WINDOWS,
+            deadlock_waits: DEFAULT_DEADLOCK_WAITS,
+            tie_breaking: TieBreaking::default(),
+            goal_parking: GoalParking::default(),
+            diagonal_crossing: DiagonalCrossing::default()
+        }
+    }
+
+    /* Agents walk at least one step between two replannings */
+    pub fn get_replan_interval(&self) -> u32 {
+        self.replan_interval.max(1)
+    }
+
+    /* Step of each replan interval at which the n-th agent replans */
+    pub fn get_replan_offset(&self, n: usize) -> u32 {
+        if self.staggered_replanning {
+            n as u32 % self.get_replan_interval()
+        } else {
+            0
+        }
+    }
+
+    pub fn is_stalled(&self, stalled_windows: u32) -> bool {
+        self.stall_windows > 0 && stalled_windows >= self.stall_windows
+    }
+
+    pub fn is_deadlocked(&self, waiting_steps: u32) -> bool {
+        self.deadlock_waits > 0 && waiting_steps >= self.deadlock_waits
+    }
+
+    /* The window must cover the steps walked until the next replanning */
+    pub fn get_window_size(&self, window_size: u32) -> u32 {
+        window_size.max(self.get_replan_interval())
+    }
+}
@@ -0,0 +1,65 @@
+use std::fmt;
+
+/* Coordination troubles noticed by WHCA* while moving the agents, with how they
+were resolved, the agents are given by task id */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoordinationEvent {
+    // Agents waiting in a cycle for the tile of the next one, they replan together
+    // and the one with the lowest priority backs off to let the others through
+    Deadlock { step: u32, agents: Vec<u32>, backing_off: u32 },
+
+    // An agent waited for several windows without getting any closer to its goal,
+    // it is planned first and the agents in its way replan after it
+    Stall { step: u32, agent: u32, windows: u32 },
+
+    // An agent kept moving for several windows without getting any closer to its goal,
+    // walking back and forth or around the others, it is resolved as a stall
+    Livelock { step: u32, agent: u32, windows: u32 },
+
+    // The agents planned before an agent left it no move over its whole window,
+    // it is planned first and every agent replans after it
+    Blocked { step: u32, agent: u32 }
+}
+
+impl CoordinationEvent {
+
+    /* Will give the event with its agents renamed by `rename` */
+    pub fn rename_agents<F: Fn(u32) -> u32>(&self, rename: F) -> CoordinationEvent {
+        match self {
+            CoordinationEvent::Deadlock { step, agents, backing_off } => CoordinationEvent::Deadlock {
+                step: *step,
+                agents: agents.iter().map(|agent| rename(*agent)).collect(),
+                backing_off: rename(*backing_off)
+            },
+            CoordinationEvent::Stall { step, agent, windows } => CoordinationEvent::Stall {
+                step: *step,
+                agent: rename(*agent),
+                windows: *windows
+            },
+            CoordinationEvent::Livelock { step, agent, windows } => CoordinationEvent::Livelock {
+                step: *step,
+                agent: rename(*agent),
+                windows: *windows
+            },
+            CoordinationEvent::Blocked { step, agent } => CoordinationEvent::Blocked {
+                step: *step,
+                agent: rename(*agent)
+            }
+        }
+    }
+}
+
+impl fmt::Display for CoordinationEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoordinationEvent::Deadlock { step, agents, backing_off } =>
+                write!(f, "step {}: agents {:?} wait on each other, agent {} backs off", step, agents, backing_off),
+            CoordinationEvent::Stall { step, agent, windows } =>
+                write!(f, "step {}: agent {} made no progress for {} windows, it is planned first", step, agent, windows),
+            CoordinationEvent::Livelock { step, agent, windows } =>
+                write!(f, "step {}: agent {} kept moving without progress for {} windows, it is planned first", step, agent, windows),
+            CoordinationEvent::Blocked { step, agent } =>
+                write!(f, "step {}: agent {} has no move left in its window, it is planned first", step, agent)
+        }
+    }
+}
@@ -1,138 +1,170 @@
-use std::borrow::{Borrow, BorrowMut};
-use std::cell::{BorrowError, Ref, RefCell};
 use std::cmp::Reverse;
 use std::collections::HashMap;
-use std::convert::TryInto;
 use priority_queue::priority_queue::PriorityQueue;
-use std::f32::consts::SQRT_2;
+use std::fmt::Debug;
 use std::hash::{Hash, Hasher};
-use std::iter::FromIterator;
-use std::rc::Rc;
-use std::thread::current;
-use math::round;
-
+use std::sync::Arc;
+use pathfinding_core::{CostProfile, Graph, GridMap, Heuristic, Octile, PathfindingError};
+
+mod agent_store;
+mod cbs;
+mod config;
+mod events;
+mod priority;
+mod rra;
+mod search_state;
+mod space_time_map;
+mod validator;
+mod whca;
+
+pub use agent_store::AgentStore;
+pub use cbs::ConflictBasedSearch;
+pub use config::{GoalParking, PlannerConfig, TieBreaking};
+pub use events::CoordinationEvent;
+pub use priority::{AgentStatus, PriorityStrategy};
+pub use rra::ReverseResumableAStar;
+pub use search_state::{DistanceStore, PagedSearchState, SearchState};
+pub use pathfinding_core::{DiagonalCrossing, TimedPath};
+pub use space_time_map::SpaceTimeMap;
+pub use validator::{PlanValidator, Violation};
+pub use whca::WhcaSolver;
+
+// Defaults of the PlannerConfig
 pub const WINDOW_SIZE: u32 = 16;
 
-type SpaceTimeMap = Vec<HashMap<(u32, u32), u32>>;
-
-pub type Agents = HashMap::<u32, Rc<RefCell<Agent>>>;
+// Agents replan their next window every REPLAN_INTERVAL steps,
+// so the reservations always cover at least half a window ahead
+pub const REPLAN_INTERVAL: u32 = WINDOW_SIZE / 2;
 
-#[derive(Default)]
+/* Grid map of the cooperative planners, each tile holds its raw traversal cost,
+u32::MAX marks an obstacle and WATER_COST a water tile. The agents read the
+tiles through their cost profile */
 pub struct WorldMap {
 
-    pub data: Vec<u32>,
-    pub width: u32,
-    pub height: u32,
+    pub grid: GridMap,
 
-    pub space_time_map: SpaceTimeMap,
-
-    pub agents: HashMap::<u32, Rc<RefCell<Agent>>>,
+    // Guides the Reverse Resumable A* toward the position being queried
+    pub heuristic: Arc<dyn Heuristic>,
 
     pub log_file: HashMap<u32, Vec<String>>
 }
 
+impl Default for WorldMap {
+    fn default() -> Self {
+        WorldMap {
+            grid: GridMap::default(),
+            heuristic: Arc::new(Octile),
+            log_file: HashMap::new()
+        }
+    }
+}
+
 impl WorldMap {
 
     pub fn new(data: Vec<u32>, width: u32, height: u32) -> WorldMap {
 
         WorldMap {
-            data,
-            width,
-            height,
-            space_time_map: std::iter::repeat(HashMap::<(u32, u32), u32>::new())
-                .take(WINDOW_SIZE as usize)
-                .collect::<SpaceTimeMap>(),
+            grid: GridMap::new(data, width, height),
 
             ..Default::default()
         }
     }
+}
 
-    pub fn get_cost(&self, pos: (u32, u32)) -> u32 {
-        self.data[(pos.1 * self.width + pos.0) as usize]
+impl From<&GridMap> for WorldMap {
+    fn from(map: &GridMap) -> Self {
+        WorldMap {
+            grid: map.clone(),
+            ..Default::default()
+        }
     }
+}
+
+/* The tiles and the moves are the ones of the grid, only the heuristic is the map's own */
+impl Graph for WorldMap {
+    type Node = (u32, u32);
 
-    pub fn set_agent(&mut self, agent: Agent) {
-        self.agents.insert(agent.id, Rc::new(RefCell::new(agent)));
+    fn get_node_count(&self) -> usize {
+        self.grid.data.len()
     }
 
-    pub fn get_neighbors(&self, position: Node) -> Vec<(u32, u32)> {
+    fn get_index(&self, pos: (u32, u32)) -> usize {
+        self.grid.get_index(pos)
+    }
 
-        let mut neighbors =  Vec::<(u32, u32)>::new();
-        let x = position.pos.0;
-        let y = position.pos.1;
+    fn get_node(&self, index: usize) -> (u32, u32) {
+        self.grid.get_node(index)
+    }
 
-        // NE
-        if x < self.width - 1 && y < self.height - 1{
-            neighbors.push((x + 1, y + 1));
-        }
-        // SW
-        if x > 0 && y > 0 {
-            neighbors.push((x - 1, y - 1));
-        }
-        // NW
-        if x > 0 && y < self.height - 1{
-            neighbors.push((x - 1, y + 1));
-        }
-        // SE
-        if x < self.width - 1 && y > 0{
-            neighbors.push((x + 1, y - 1));
-        }
-        // E
-        if x < self.width - 1 {
-            neighbors.push((x + 1, y));
-        }
-        // W
-        if x > 0 {
-            neighbors.push((x - 1, y));
-        }
-        // N
-        if y < self.height - 1 {
-            neighbors.push((x, y + 1));
-        }
-        // S
-        if y > 0 {
-            neighbors.push((x, y - 1));
-        }
+    fn contains(&self, pos: (u32, u32)) -> bool {
+        self.grid.contains(pos)
+    }
+
+    fn is_obstacle(&self, pos: (u32, u32)) -> bool {
+        self.grid.is_obstacle_for(pos, &CostProfile::default())
+    }
 
-        neighbors
+    fn get_neighbors(&self, pos: (u32, u32)) -> Vec<((u32, u32), u32)> {
+        self.grid.get_neighbors_for(pos, &CostProfile::default())
+    }
 
+    fn get_predecessors(&self, pos: (u32, u32)) -> Vec<((u32, u32), u32)> {
+        self.grid.get_predecessors_for(pos, &CostProfile::default())
     }
 
-    pub fn is_obstacle(&self, node: Node) -> bool {
+    fn get_wait_cost(&self, pos: (u32, u32)) -> u32 {
+        self.grid.get_wait_cost_for(pos, &CostProfile::default())
+    }
 
-        return if self.get_cost(node.pos) == u32::MAX {
-            true
-        } else {
-            false
-        }
+    fn is_obstacle_for(&self, pos: (u32, u32), profile: &CostProfile) -> bool {
+        self.grid.is_obstacle_for(pos, profile)
+    }
+
+    fn get_neighbors_for(&self, pos: (u32, u32), profile: &CostProfile) -> Vec<((u32, u32), u32)> {
+        self.grid.get_neighbors_for(pos, profile)
+    }
 
+    fn get_predecessors_for(&self, pos: (u32, u32), profile: &CostProfile) -> Vec<((u32, u32), u32)> {
+        self.grid.get_predecessors_for(pos, profile)
     }
 
-    pub fn get(&self, x: u32, y: u32) -> u32 {
-        self.data[(y * self.width + x) as usize]
+    fn get_wait_cost_for(&self, pos: (u32, u32), profile: &CostProfile) -> u32 {
+        self.grid.get_wait_cost_for(pos, profile)
     }
 
-    // We use Manhattan distance to calculate
-    // right angle distance between start and goal
-    pub fn manhattan_distance(a: Node, b: Node) -> u32 {
-        (round::ceil(a.pos.0 as f64 - b.pos.0 as f64, 0) + round::ceil(a.pos.1 as f64 - b.pos.1 as f64, 0)) as u32
+    fn heuristic(&self, from: (u32, u32), to: (u32, u32)) -> u32 {
+        self.heuristic.distance(from, to)
+    }
+
+    fn get_crossing_edges(&self, from: (u32, u32), to: (u32, u32)) -> Vec<((u32, u32), (u32, u32))> {
+        self.grid.get_crossing_edges(from, to)
     }
 }
 
 #[derive(Debug, Clone, Copy, Default, Eq)]
-pub struct Node {
-    pub pos :(u32, u32),
+pub struct Node<P = (u32, u32)> {
+    pub pos: P,
     pub g_score: u32,
     pub f_score: u32
 }
 
-impl PartialEq for Node {
+impl<P> Node<P> {
+    pub fn new(pos: P, g_score: u32) -> Node<P> {
+        Node {
+            pos,
+            g_score,
+            f_score: 0
+        }
+    }
+}
+
+impl<P: PartialEq> PartialEq for Node<P> {
     fn eq(&self, other: &Self) -> bool {
          self.pos == other.pos
     }
 }
 
-impl Hash for Node {
+impl<P: Hash> Hash for Node<P> {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.pos.hash(state);
     }
@@ -148,300 +180,305 @@ impl From<(u32, u32, u32)> for Node {
     }
 }
 
-#[derive(Default, Debug)]
-pub struct Agent {
-
-    pub came_from: HashMap<Node, Node>,
-    pub cost_so_far: HashMap<(u32, u32), Node>,
-    pub closed_set: HashMap<Node, u32>,
-
-    // This priority queue will be ordered by the reverse of the highest cost
-    // so, the priority of nodes exploration will depend of their accessibility cost
-    pub open_set: PriorityQueue<Node, Reverse<u32>>,
+/* Agent of the Windowed Hierarchical Cooperative A*, P is the node type of the graph it walks on */
+#[derive(Debug)]
+pub struct Agent<P: Eq + Hash = (u32, u32)> {
 
     id: u32,
     pub name: String,
-    start: Node,
-    pub goal: Node,
-    pub current_node: Node,
-    previous_node: Node,
-    next_node: Node,
+    start: Node<P>,
+    pub goal: Node<P>,
+    pub current_node: Node<P>,
+
+    path: Vec<Node<P>>,
+    pub portion_path: Vec<Node<P>>,
+
+    // Terrain the agent can cross and at what cost, its heuristic must share it
+    pub profile: CostProfile,
+
+    // Steps planned by each space-time search of this agent
+    pub window_size: u32,
+    pub tie_breaking: TieBreaking,
+
+    // The agent replans at the steps equal to this offset modulo the replan interval
+    pub replan_offset: u32,
+
+    // Steps spent waiting away from the goal, in total and in a row up to now
+    pub delay: u32,
+    pub waiting_steps: u32,
 
-    path: Vec<Node>,
-    pub portion_path: Vec<Node>,
+    // Windows planned in a row without getting closer to the goal than best_distance
+    pub stalled_windows: u32,
+    best_distance: u32,
 
-    is_walking: bool
+    // Moves made since the last window planned on schedule, a stalled agent
+    // walking back and forth makes some where a waiting one makes none
+    pub window_moves: u32,
+
+    pub goal_parking: GoalParking,
+
+    // Left the map on arrival, it isn't planned anymore
+    pub has_vanished: bool,
+
+    // Nodes expanded by the space-time searches of this agent,
+    // and by the RRA* when its queries resumed the search
+    pub nodes_expanded: usize
 }
 
-impl Agent {
+impl<P: Copy + Eq + Hash + Debug> Agent<P> {
 
-    pub fn new(id: u32, name: &str) -> Agent{
+    pub fn new(id: u32, name: &str, start: Node<P>, goal: Node<P>, profile: CostProfile, config: &PlannerConfig) -> Agent<P> {
         Agent {
             id,
             name: name.into(),
-            portion_path : Vec::with_capacity(WINDOW_SIZE as usize),
-            ..Default::default()
+            start,
+            goal,
+            current_node: start,
+            path: vec![start],
+            portion_path : Vec::with_capacity(config.window_size as usize),
+            profile,
+            window_size: config.get_window_size(config.window_size),
+            tie_breaking: config.tie_breaking,
+            replan_offset: 0,
+            delay: 0,
+            waiting_steps: 0,
+            stalled_windows: 0,
+            best_distance: u32::MAX,
+            window_moves: 0,
+            goal_parking: config.goal_parking,
+            has_vanished: false,
+            nodes_expanded: 0
         }
     }
 
-    pub fn set_start(&mut self, start: Node) {
+    pub fn set_start(&mut self, start: Node<P>) {
         self.start = start;
         self.current_node = start;
         self.path.push(start);
     }
 
-    pub fn get_start(&self) -> Node {
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn get_start(&self) -> Node<P> {
         self.start
     }
 
-    pub fn set_goal(&mut self, goal: Node) {
+    pub fn set_goal(&mut self, goal: Node<P>) {
         self.goal = goal;
     }
 
-    pub fn get_goal(&self) -> Node {
+    pub fn get_goal(&self) -> Node<P> {
         self.goal
     }
 
-    pub fn print_heuristic(&self, map: &WorldMap) {
-
-        for y in 0..map.height {
-            for x in 0..map.width {
-                let score = match self.cost_so_far.get(&(x, y)) {
-                    Some(node) => {
-
-                        let score = node.f_score;
-
-                        if score == u32::MAX {
-                            print!("  #");
-                        }else{
-                            if score < 10 {
-                                print!("  {:?}", score);
-                            } else {
-                                print!(" {:?}", score);
-                            }
-                        }
-                    }
-                    None => {
-
-                        let el = map.get_cost((x, y));
-                        if el == u32::MAX {
-                            print!("  #",);
-                        }else{
-                            if el < 10 {
-                                print!("  {:?}", el);
-                            } else {
-                                print!(" {:?}", el);
-                            }
-                        }
-                    }
-                };
-            }
-            print!("\n");
-        }
-    }
+    /* Will give the true distance from pos to the goal, the heuristic
+    being the Reverse Resumable A* shared by the agents heading to the goal */
+    fn true_distance<G: Graph<Node = P>>(&mut self, graph: &G, pos: P, heuristic: &mut ReverseResumableAStar<P>) -> u32 {
 
-    fn process_neighbors(&mut self, current_pos: Node, next_best: Node, map: &WorldMap, time: u32, agents: &Agents) -> Node {
-
-        let mut best_neighbor = current_pos;
-
-        /* If this is the case, get availables neighbors,
-        if there is no match in the cost hashmap,
-        we reload the partial search to expand new nodes
-        */
-
-        for neighbor_pos in map.get_neighbors(current_pos) {
-            if neighbor_pos != next_best.pos {
-                if map.space_time_map[time as usize].get(&neighbor_pos).is_none() {
-                    let neighbor = match self.cost_so_far.get(&neighbor_pos) {
-                        None => {
-                            self.get_true_distance_heuristic(map,
-                                                             Node::from((neighbor_pos.0, neighbor_pos.1, map.get_cost(neighbor_pos))),
-                                                             self.goal,
-                                                             &agents
-                            );
-                            self.cost_so_far.get(&neighbor_pos).unwrap()
-                        }
-                        Some(node) => {
-                            node
-                        }
-                    };
-
-                    if neighbor.f_score <= best_neighbor.f_score {
-                        best_neighbor = *neighbor;
-                    }
-                }
-            }
-        }
-        best_neighbor
+        let nodes_expanded = heuristic.nodes_expanded;
+        let distance = heuristic.get_distance(graph, pos).unwrap_or(u32::MAX);
 
+        self.nodes_expanded += heuristic.nodes_expanded - nodes_expanded;
+        distance
     }
 
-    /* Will calculate the path depending of agents position in the space-time map */
-    pub fn set_portion_path(&mut self, map: &mut WorldMap, agents: &Agents) {
-
-        self.is_walking = true;
-
-        let mut next_best;
-
-        for i in 0..WINDOW_SIZE {
+    /* Will give the node of the best move toward the goal, the other agents ignored,
+    None when the agent is on its goal or can't move */
+    pub fn get_next_node<G: Graph<Node = P>>(&mut self, graph: &G, heuristic: &mut ReverseResumableAStar<P>) -> Option<P> {
+        self.get_best_move(graph, self.current_node.pos, heuristic)
+    }
 
-            /* If goal already found, agent stay in place and block the tile*/
-            if self.current_node == self.goal {
+    /* Will give the nodes of the best moves toward the goal over the next window, the other agents ignored,
+    the route stops early on the goal */
+    pub fn get_route<G: Graph<Node = P>>(&mut self, graph: &G, heuristic: &mut ReverseResumableAStar<P>) -> Vec<P> {
+        let mut route = Vec::new();
+        let mut pos = self.current_node.pos;
 
-                self.portion_path.push(self.current_node);
-                map.space_time_map[i as usize].insert(self.current_node.pos, self.id);
-                self.is_walking = false;
-                map.data[(self.current_node.pos.1 * map.width + self.current_node.pos.0) as usize] = 100;
+        while route.len() < self.window_size as usize {
+            match self.get_best_move(graph, pos, heuristic) {
+                Some(next) => pos = next,
+                None => break
+            }
+            route.push(pos);
+        }
 
-            } else {
-                next_best = self.came_from[&self.current_node];
+        route
+    }
 
-                /* This Node is already occupied by another agent ? (excepted current) */
-                if !map.space_time_map[i as usize].get(&next_best.pos).is_none()
-                    && map.space_time_map[i as usize].get(&next_best.pos).unwrap() != &self.id {
+    fn get_best_move<G: Graph<Node = P>>(&mut self, graph: &G, pos: P, heuristic: &mut ReverseResumableAStar<P>) -> Option<P> {
 
-                    let best_neighbor = self.process_neighbors(self.current_node, next_best, map, i, &agents);
+        if pos == self.goal.pos {
+            return None;
+        }
 
-                    /* if the new neighbor is more costly than current position, the agent will stop for one tick */
-                    if best_neighbor.f_score <= self.current_node.f_score {
-                        map.space_time_map[i as usize].insert(self.current_node.pos, self.id);
-                        next_best = self.current_node;
-                    }else{
-                        map.space_time_map[i as usize].insert(best_neighbor.pos, self.id);
-                        next_best = best_neighbor;
-                    }
+        let mut best = None;
+        let mut best_cost = u32::MAX;
 
+        for (next, move_cost) in graph.get_neighbors_for(pos, &self.profile) {
+            let cost = move_cost.saturating_add(self.true_distance(graph, next, heuristic));
 
-                /* Otherwise, we test if another agent get the risk to overlap current */
-                } else if i > 0 && !map.space_time_map[(i - 1) as usize].get(&next_best.pos).is_none()
-                    && !map.space_time_map[i as usize].get(&self.current_node.pos).is_none(){
+            if cost < best_cost {
+                best = Some(next);
+                best_cost = cost;
+            }
+        }
 
-                    let best_neighbor = self.process_neighbors(self.current_node, next_best, map, i, &agents);
+        best
+    }
 
-                    map.space_time_map[i as usize].insert(best_neighbor.pos, self.id);
-                    next_best = best_neighbor;
+    /* Will count the windows in a row the agent didn't get any closer to its goal than it ever was,
+    called once per window planned on schedule, the extra replannings of a step don't count */
+    fn update_progress<G: Graph<Node = P>>(&mut self, graph: &G, heuristic: &mut ReverseResumableAStar<P>) {
+        let distance = self.true_distance(graph, self.current_node.pos, heuristic);
 
-                } else {
-                    map.space_time_map[i as usize].insert(next_best.pos, self.id);
-                }
+        if distance < self.best_distance || distance == 0 {
+            self.best_distance = distance;
+            self.stalled_windows = 0;
+        } else {
+            self.stalled_windows += 1;
+        }
 
-                self.portion_path.push(next_best);
-                self.current_node = next_best;
-            }
+        self.window_moves = 0;
+    }
 
+    fn get_status<G: Graph<Node = P>>(&self, graph: &G) -> AgentStatus {
+        AgentStatus {
+            id: self.id,
+            distance: graph.heuristic(self.current_node.pos, self.goal.pos),
+            is_parked: self.current_node == self.goal && self.goal_parking == GoalParking::Stay,
+            delay: self.delay,
+            stalled_windows: self.stalled_windows
         }
-        self.portion_path.reverse();
     }
 
-    /* Will calculate the g_score by running a Reverse Resumable A* */
-    pub fn get_true_distance_heuristic(&mut self, map: &WorldMap, agent_start: Node, agent_goal: Node, agents: &Agents) -> bool {
+    /* Will search the best sequence of moves over the next window in the
+    space-time map, states are (node, t) and waiting is an explicit action.
+    A state is stored at the index t * node_count + node index of the search state */
+    fn space_time_search<G: Graph<Node = P>>(&mut self, graph: &G, space_time_map: &SpaceTimeMap<P>, heuristic: &mut ReverseResumableAStar<P>, state: &mut SearchState) -> Result<Vec<Node<P>>, PathfindingError<P>> {
 
-        let mut start = agent_goal;
-        let goal =  agent_start;
+        let node_count = graph.get_node_count();
+        let start = graph.get_index(self.current_node.pos);
 
-        if map.is_obstacle(goal) {
-            return false;
-        }
+        // Ordered by the lowest f score, then by the tie-breaking of the agent
+        let mut open_set = PriorityQueue::<usize, Reverse<(u32, u32)>>::new();
 
-        start.g_score = 0;
-        start.f_score = WorldMap::manhattan_distance(start, goal);
+        state.reset(node_count * (self.window_size as usize + 1));
 
-        self.cost_so_far.insert(start.pos, start);
-        self.open_set.push(start, Reverse(WorldMap::manhattan_distance(start, goal)));
+        let start_h = self.true_distance(graph, self.current_node.pos, heuristic);
 
-        while let Some((current, Reverse(_current_cost))) = self.open_set.pop() {
+        if start_h == u32::MAX {
+            return Err(PathfindingError::GoalUnreachable { start: self.current_node.pos, goal: self.goal.pos });
+        }
 
-            self.closed_set.insert(current, current.f_score);
+        state.set(start, 0, start);
+        open_set.push(start, Reverse((start_h, self.tie_breaking.get_priority(0))));
 
-            for mut next_pos in map.get_neighbors(current) {
+        while let Some((current, _)) = open_set.pop() {
 
-                let mut next: Node = Default::default();
+            let pos = graph.get_node(current % node_count);
+            let time = (current / node_count) as u32;
+            let current_cost = state.get_g_score(current);
+            self.nodes_expanded += 1;
 
-                match self.cost_so_far.get(&next_pos) {
-                    None => {
-                        next = Node {
-                            pos: next_pos,
-                            g_score: u32::MAX,
-                            f_score: u32::MAX
-                        };
-                        self.cost_so_far.insert(next.pos, next);
+            /* The window is fully planned, rebuild the moves from the start */
+            if time == self.window_size {
+                let mut path = Vec::<Node<P>>::with_capacity(self.window_size as usize);
+                let mut index = current;
 
-                    }
-                    Some(_) => {}
+                while let Some(parent) = state.get_parent(index) {
+                    path.push(Node::new(graph.get_node(index % node_count), state.get_g_score(index)));
+                    index = parent;
                 }
+                path.reverse();
+                return Ok(path);
+            }
 
-                let mut blocked_by_stopped_agent = false;
-
-                for i in 1..&agents.len() + 1 {
+            // Waiting comes first, then every move of the graph
+            let mut moves = vec![(pos, graph.get_wait_cost_for(pos, &self.profile))];
+            moves.extend(graph.get_neighbors_for(pos, &self.profile));
 
-                    let mut rc = &agents.get(&(i as u32)).unwrap();
-                    let mut agent = &mut *rc.borrow_mut();
+            for (next, move_cost) in moves {
 
-                    match agent.try_borrow() {
-                        Ok(agent) if agent.current_node == next && !agent.is_walking => {
-                            blocked_by_stopped_agent = true;
-                        }
-                        Err(err) => {
-                        }
-                        _ => {
-                        }
-                    };
-                }
-             /*   if blocked_by_stopped_agent {
-                    println!("agent {:?} is blocked by another agent at {:?}", agent_start, next_pos);
+                if !space_time_map.is_move_free(graph, time, pos, next, self.id) {
+                    continue;
                 }
 
-                if map.is_obstacle(next) {
-                    println!("agent {:?} is blocked by obstacle at  {:?}", agent_start, next);
-                }*/
+                let h = self.true_distance(graph, next, heuristic);
 
-                if !map.is_obstacle(next) && !blocked_by_stopped_agent {
+                if h == u32::MAX {
+                    continue;
+                }
 
-                    let new_cost = {
+                /* Waiting on the goal is free, so the agent can rest there until the window ends */
+                let move_cost = if next == pos && next == self.goal.pos {
+                    0
+                } else {
+                    move_cost
+                };
 
-                      /*  We take the current node cost incremented
-                        from the cost of next node
-                        If diagonal, add an extra cost for traversing */
-                        if current.pos.0 != next.pos.0 && current.pos.1 != next.pos.1 {
-                            current.g_score + f32::floor(map.get_cost(next.pos) as f32 * SQRT_2) as u32
-                        } else {
-                            current.g_score + map.get_cost(next.pos)
-                        }
-                    };
+                let new_cost = current_cost.saturating_add(move_cost);
+                let next_state = (time as usize + 1) * node_count + graph.get_index(next);
 
-                    if self.closed_set.get(&next).is_none() || new_cost <= self.cost_so_far[&next.pos].f_score {
+                if new_cost < state.get_g_score(next_state) {
+                    state.set(next_state, new_cost, current);
+                    open_set.push_increase(next_state, Reverse((new_cost.saturating_add(h), self.tie_breaking.get_priority(new_cost))));
+                }
+            }
+        }
 
-                        self.closed_set.insert(next, next.g_score);
+        Err(PathfindingError::WindowExhausted { agent: self.id, pos: self.current_node.pos })
+    }
+
+    /* Will plan the next window from the current position, replacing the reservations of the agent,
+    the heuristic must be the one searching backward from the goal of the agent */
+    pub fn replan<G: Graph<Node = P>>(&mut self, graph: &G, space_time_map: &mut SpaceTimeMap<P>, heuristic: &mut ReverseResumableAStar<P>, state: &mut SearchState) -> Result<(), PathfindingError<P>> {
+        space_time_map.release(self.id);
+        self.portion_path.clear();
+        self.set_portion_path(graph, space_time_map, heuristic, state)
+    }
 
-                        next.g_score = new_cost;
-                        next.f_score = new_cost + WorldMap::manhattan_distance(next, goal);
+    /* Will calculate the path depending of agents position in the space-time map */
+    pub fn set_portion_path<G: Graph<Node = P>>(&mut self, graph: &G, space_time_map: &mut SpaceTimeMap<P>, heuristic: &mut ReverseResumableAStar<P>, state: &mut SearchState) -> Result<(), PathfindingError<P>> {
 
-                        *self.cost_so_far.get_mut(&next.pos).unwrap() = next;
-                        self.came_from.insert(next, current);
+        for node in [self.current_node, self.goal].iter() {
+            if !graph.contains(node.pos) {
+                return Err(PathfindingError::OutOfBounds(node.pos));
+            }
+        }
 
-                        //Update priority queue with this new cost
-                        self.open_set.push_decrease(next, Reverse(next.f_score));
+        /* Waiting on the goal is free, so an agent parked there keeps it reserved
+        and only moves off when another agent already reserved the tile */
+        let path = self.space_time_search(graph, space_time_map, heuristic, state)?;
 
-                    }
+        let mut previous = self.current_node;
 
-                }else{
-                    self.closed_set.insert(next, u32::MAX);
-                }
-            }
+        for (i, node) in path.iter().enumerate() {
+            space_time_map.reserve(i as u32, previous.pos, node.pos, self.id);
+            self.portion_path.push(*node);
+            previous = *node;
+        }
 
-            if goal.pos == current.pos {
-                break;
-            }
+        // Ending its window on its goal, an agent that stays keeps the goal from its last arrival on,
+        // so the agents planning further ahead than its window don't plan through it
+        if self.goal_parking == GoalParking::Stay && path.last() == Some(&self.goal) {
+            let arrival = path.iter().rposition(|node| *node != self.goal).map_or(0, |i| i + 1);
+            space_time_map.park(arrival as u32, self.goal.pos, self.id);
         }
-        true
+
+        // Standing on its goal, a yielding agent lets the others plan through it, even while it
+        // steps aside for one of them, one still arriving has moves the others must respect
+        let is_yielding = self.goal_parking == GoalParking::Yield && self.current_node.pos == self.goal.pos;
+        space_time_map.set_yielding(self.id, is_yielding);
+
+        self.portion_path.reverse();
+        Ok(())
     }
 }
 
-
-impl PartialEq for Agent {
+impl<P: Eq + Hash> PartialEq for Agent<P> {
     fn eq(&self, other: &Self) -> bool {
         self.name == other.name
     }
 }
-
-
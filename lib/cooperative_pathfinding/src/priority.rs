@@ -0,0 +1,60 @@
+use std::cmp::Reverse;
+
+/* What the priority strategies know of an agent about to replan */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AgentStatus {
+    pub id: u32,
+
+    // Distance from the agent to its goal estimated by the heuristic of the graph
+    pub distance: u32,
+
+    // Resting on its goal, its reservations come before the others'
+    pub is_parked: bool,
+
+    // Steps spent waiting away from the goal
+    pub delay: u32,
+
+    // Windows replanned in a row without getting closer to the goal
+    pub stalled_windows: u32
+}
+
+/* Order in which the agents replanning on the same step are planned,
+the first ones get the first pick of the reservations */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PriorityStrategy {
+    // By increasing ids, the order of the tasks
+    #[default]
+    Fixed,
+
+    // Shuffled on every step, the same seed gives the same orders
+    Random { seed: u64 },
+
+    FarthestFirst,
+
+    MostDelayedFirst
+}
+
+impl PriorityStrategy {
+
+    /* Will sort the agents by decreasing priority, ties are kept by increasing ids */
+    pub fn sort(&self, agents: &mut [AgentStatus], steps: u32) {
+
+        agents.sort_by_key(|agent| agent.id);
+
+        match self {
+            PriorityStrategy::Fixed => {}
+            PriorityStrategy::Random { seed } => {
+
+                // Fisher-Yates shuffle driven by a linear congruential generator
+                let mut state = seed ^ (steps as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+
+                for i in (1..agents.len()).rev() {
+                    state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                    agents.swap(i, ((state >> 33) % (i as u64 + 1)) as usize);
+                }
+            }
+            PriorityStrategy::FarthestFirst => agents.sort_by_key(|agent| Reverse(agent.distance)),
+            PriorityStrategy::MostDelayedFirst => agents.sort_by_key(|agent| Reverse(agent.delay))
+        }
+    }
+}
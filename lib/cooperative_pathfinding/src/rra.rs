@@ -0,0 +1,142 @@
+use std::cmp::Reverse;
+use priority_queue::priority_queue::PriorityQueue;
+use pathfinding_core::{CostProfile, Graph};
+
+use crate::{DistanceStore, PagedSearchState, WorldMap};
+
+/* Reverse Resumable A*, searches backward from a goal and resumes lazily each time
+the true distance of a node that isn't expanded yet is asked. Its heuristic aims at the
+target, usually the start of the first agent heading to the goal, but the distances
+stay exact for any node, so every agent sharing the goal and the cost profile can share the search.
+S stores the distances, by default only for the part of the graph the search reaches */
+#[derive(Debug, Clone)]
+pub struct ReverseResumableAStar<P = (u32, u32), S = PagedSearchState> {
+    goal: P,
+    target: P,
+
+    // Distances are the ones of an agent crossing the terrain with this profile
+    profile: CostProfile,
+
+    // Distances to the goal and expanded nodes, indexed by the nodes of the graph
+    state: S,
+
+    // Node indexes ordered by the lowest f score, the distance to the goal plus the estimate to the target
+    open_set: PriorityQueue<usize, Reverse<u32>>,
+
+    is_seeded: bool,
+
+    pub nodes_expanded: usize
+}
+
+impl<P: Copy> ReverseResumableAStar<P> {
+
+    pub fn new(goal: P, target: P, profile: CostProfile) -> ReverseResumableAStar<P> {
+        ReverseResumableAStar::with_state(goal, target, profile, PagedSearchState::new())
+    }
+}
+
+impl<P: Copy, S: DistanceStore> ReverseResumableAStar<P, S> {
+
+    /* The same search storing its distances in the given store, e.g. a SearchState with a slot per node */
+    pub fn with_state(goal: P, target: P, profile: CostProfile, state: S) -> ReverseResumableAStar<P, S> {
+        ReverseResumableAStar {
+            goal,
+            target,
+            profile,
+            state,
+            open_set: PriorityQueue::new(),
+            is_seeded: false,
+            nodes_expanded: 0
+        }
+    }
+
+    pub fn get_goal(&self) -> P {
+        self.goal
+    }
+
+    pub fn get_profile(&self) -> CostProfile {
+        self.profile
+    }
+
+    pub fn is_expanded<G: Graph<Node = P>>(&self, graph: &G, pos: P) -> bool {
+        self.is_seeded && graph.contains(pos) && self.state.is_closed(graph.get_index(pos))
+    }
+
+    /* Will give the true distance from pos to the goal, resuming the search
+    until pos is expanded, None when the goal can't be reached from pos */
+    pub fn get_distance<G: Graph<Node = P>>(&mut self, graph: &G, pos: P) -> Option<u32> {
+
+        if !graph.contains(pos) {
+            return None;
+        }
+
+        // The search is only seeded on the first query, when the graph is known
+        if !self.is_seeded {
+            self.is_seeded = true;
+            self.state.reset(graph.get_node_count());
+
+            if graph.contains(self.goal) && !graph.is_obstacle_for(self.goal, &self.profile) {
+                let goal_index = graph.get_index(self.goal);
+
+                self.state.set_g_score(goal_index, 0);
+                self.open_set.push(goal_index, Reverse(graph.heuristic(self.target, self.goal)));
+            }
+        }
+
+        let index = graph.get_index(pos);
+
+        if self.state.is_closed(index) {
+            return Some(self.state.get_g_score(index));
+        }
+
+        while let Some((current_index, _)) = self.open_set.pop() {
+
+            self.state.close(current_index);
+            self.nodes_expanded += 1;
+
+            let current = graph.get_node(current_index);
+            let current_cost = self.state.get_g_score(current_index);
+
+            // The search runs backward, so it follows the moves leading to current
+            for (previous, move_cost) in graph.get_predecessors_for(current, &self.profile) {
+
+                let previous_index = graph.get_index(previous);
+
+                if graph.is_obstacle_for(previous, &self.profile) || self.state.is_closed(previous_index) {
+                    continue;
+                }
+
+                let new_cost = current_cost.saturating_add(move_cost);
+
+                if new_cost < self.state.get_g_score(previous_index) {
+                    self.state.set_g_score(previous_index, new_cost);
+                    self.open_set.push_increase(previous_index, Reverse(new_cost.saturating_add(graph.heuristic(self.target, previous))));
+                }
+            }
+
+            if current_index == index {
+                return Some(current_cost);
+            }
+        }
+
+        None
+    }
+}
+
+impl<S: DistanceStore> ReverseResumableAStar<(u32, u32), S> {
+
+    /* Will print the distances found so far, # for the obstacles and the nodes not expanded yet */
+    pub fn print(&self, map: &WorldMap) {
+
+        for y in 0..map.grid.height {
+            for x in 0..map.grid.width {
+                if self.is_expanded(map, (x, y)) {
+                    print!(" {:>4}", self.state.get_g_score(map.get_index((x, y))));
+                } else {
+                    print!("    #");
+                }
+            }
+            println!();
+        }
+    }
+}
@@ -0,0 +1,196 @@
+/* Scores and parents of the states of a search, stored in flat arrays indexed like the states.
+A slot only holds a value if it was written since the last reset, so a reset bumps the
+generation instead of clearing the arrays and the buffers are reused from one search to the next.
+The windowed space-time searches share one, the per-goal searches use a PagedSearchState */
+#[derive(Debug, Clone, Default)]
+pub struct SearchState {
+    g_scores: Vec<u32>,
+    parents: Vec<usize>,
+    closed: Vec<bool>,
+
+    // Generation in which each slot was last written
+    generations: Vec<u32>,
+    generation: u32
+}
+
+impl SearchState {
+
+    pub fn new() -> SearchState {
+        SearchState {
+            ..Default::default()
+        }
+    }
+
+    /* Will forget every state, the arrays only grow when more than len states are needed */
+    pub fn reset(&mut self, len: usize) {
+
+        if self.generations.len() < len {
+            self.g_scores.resize(len, u32::MAX);
+            self.parents.resize(len, 0);
+            self.closed.resize(len, false);
+            self.generations.resize(len, 0);
+        }
+
+        self.generation = self.generation.wrapping_add(1);
+
+        // After a wrap around, old slots could look written in the new generation
+        if self.generation == 0 {
+            self.generations.iter_mut().for_each(|generation| *generation = 0);
+            self.generation = 1;
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.generations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.generations.is_empty()
+    }
+
+    fn is_written(&self, index: usize) -> bool {
+        self.generations[index] == self.generation
+    }
+
+    /* Lowest cost found to the state, u32::MAX if it wasn't reached yet */
+    pub fn get_g_score(&self, index: usize) -> u32 {
+        if self.is_written(index) {
+            self.g_scores[index]
+        } else {
+            u32::MAX
+        }
+    }
+
+    pub fn get_parent(&self, index: usize) -> Option<usize> {
+        if self.is_written(index) && self.parents[index] != index {
+            Some(self.parents[index])
+        } else {
+            None
+        }
+    }
+
+    pub fn is_closed(&self, index: usize) -> bool {
+        self.is_written(index) && self.closed[index]
+    }
+
+    /* A state being its own parent starts the search */
+    pub fn set(&mut self, index: usize, g_score: u32, parent: usize) {
+        if !self.is_written(index) {
+            self.closed[index] = false;
+            self.generations[index] = self.generation;
+        }
+
+        self.g_scores[index] = g_score;
+        self.parents[index] = parent;
+    }
+
+    /* Only a state already reached can be closed */
+    pub fn close(&mut self, index: usize) {
+        if self.is_written(index) {
+            self.closed[index] = true;
+        }
+    }
+}
+
+// Nodes per page of a PagedSearchState
+const PAGE_SIZE: usize = 64;
+
+/* What a Reverse Resumable A* keeps of each node: its distance to the goal and whether it is expanded */
+pub trait DistanceStore: Default {
+
+    /* Will forget every node, the nodes are then indexed from 0 to len */
+    fn reset(&mut self, len: usize);
+
+    /* Lowest cost found from the node to the goal, u32::MAX if it wasn't reached yet */
+    fn get_g_score(&self, index: usize) -> u32;
+
+    fn set_g_score(&mut self, index: usize, g_score: u32);
+
+    fn is_closed(&self, index: usize) -> bool;
+
+    fn close(&mut self, index: usize);
+}
+
+/* The dense arrays, a slot per node whatever the part of the graph the search reaches */
+impl DistanceStore for SearchState {
+    fn reset(&mut self, len: usize) {
+        SearchState::reset(self, len);
+    }
+
+    fn get_g_score(&self, index: usize) -> u32 {
+        SearchState::get_g_score(self, index)
+    }
+
+    fn set_g_score(&mut self, index: usize, g_score: u32) {
+        self.set(index, g_score, index);
+    }
+
+    fn is_closed(&self, index: usize) -> bool {
+        SearchState::is_closed(self, index)
+    }
+
+    fn close(&mut self, index: usize) {
+        SearchState::close(self, index);
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Page {
+    g_scores: [u32; PAGE_SIZE],
+    closed: [bool; PAGE_SIZE]
+}
+
+/* Distances of a search reaching a small part of a large graph. The nodes are split in pages of
+consecutive indexes, a page is only allocated once one of its nodes is reached, so each goal
+only costs the part of the map its search went through */
+#[derive(Debug, Clone, Default)]
+pub struct PagedSearchState {
+    pages: Vec<Option<Box<Page>>>
+}
+
+impl PagedSearchState {
+
+    pub fn new() -> PagedSearchState {
+        PagedSearchState::default()
+    }
+
+    /* Pages allocated so far */
+    pub fn get_page_count(&self) -> usize {
+        self.pages.iter().filter(|page| page.is_some()).count()
+    }
+
+    fn get_page(&self, index: usize) -> Option<&Page> {
+        self.pages[index / PAGE_SIZE].as_deref()
+    }
+}
+
+impl DistanceStore for PagedSearchState {
+    fn reset(&mut self, len: usize) {
+        self.pages.clear();
+        self.pages.resize_with(len.div_ceil(PAGE_SIZE), || None);
+    }
+
+    fn get_g_score(&self, index: usize) -> u32 {
+        self.get_page(index).map_or(u32::MAX, |page| page.g_scores[index % PAGE_SIZE])
+    }
+
+    fn set_g_score(&mut self, index: usize, g_score: u32) {
+        let page = self.pages[index / PAGE_SIZE].get_or_insert_with(|| Box::new(Page {
+            g_scores: [u32::MAX; PAGE_SIZE],
+            closed: [false; PAGE_SIZE]
+        }));
+
+        page.g_scores[index % PAGE_SIZE] = g_score;
+    }
+
+    fn is_closed(&self, index: usize) -> bool {
+        self.get_page(index).is_some_and(|page| page.closed[index % PAGE_SIZE])
+    }
+
+    /* Only a node already reached can be closed */
+    fn close(&mut self, index: usize) {
+        if let Some(page) = self.pages[index / PAGE_SIZE].as_deref_mut() {
+            page.closed[index % PAGE_SIZE] = true;
+        }
+    }
+}
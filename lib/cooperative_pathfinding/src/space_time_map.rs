@@ -0,0 +1,183 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use pathfinding_core::{DiagonalCrossing, Graph};
+
+/* Reservation table of the window, slice t holds the nodes occupied at
+the end of the t-th next step and the edges traversed during it */
+#[derive(Debug, Clone)]
+pub struct SpaceTimeMap<P = (u32, u32)> {
+    vertices: Vec<HashMap<P, u32>>,
+    edges: Vec<HashMap<(P, P), u32>>,
+
+    // Agents parked on their goal letting the others plan through it,
+    // their tiles only hold for the agents yielding as well
+    yielding: HashSet<u32>,
+
+    // Yielding agents whose tile was taken since the last call to take_displaced
+    displaced: Vec<u32>,
+
+    // Goals of the agents staying there for good, with the agent and the step of the window it arrives,
+    // they stay reserved after the window ends
+    parked: HashMap<P, (u32, u32)>,
+
+    pub diagonal_crossing: DiagonalCrossing
+}
+
+impl<P: Copy + Eq + Hash> Default for SpaceTimeMap<P> {
+    fn default() -> Self {
+        SpaceTimeMap::new(0)
+    }
+}
+
+impl<P: Copy + Eq + Hash> SpaceTimeMap<P> {
+
+    pub fn new(window_size: u32) -> SpaceTimeMap<P> {
+        SpaceTimeMap {
+            vertices: vec![HashMap::new(); window_size as usize],
+            edges: vec![HashMap::new(); window_size as usize],
+            yielding: HashSet::new(),
+            displaced: Vec::new(),
+            parked: HashMap::new(),
+            diagonal_crossing: DiagonalCrossing::default()
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    /* Drop every reservation of the window */
+    pub fn clear(&mut self) {
+        self.vertices.iter_mut().for_each(HashMap::clear);
+        self.edges.iter_mut().for_each(HashMap::clear);
+        self.parked.clear();
+    }
+
+    /* Drop the reservations of one agent, leaving the others' untouched */
+    pub fn release(&mut self, id: u32) {
+        self.vertices.iter_mut().for_each(|slice| slice.retain(|_, occupant| *occupant != id));
+        self.edges.iter_mut().for_each(|slice| slice.retain(|_, occupant| *occupant != id));
+        self.parked.retain(|_, (occupant, _)| *occupant != id);
+    }
+
+    /* Drop the expired time slice and open a new one at the end of the window */
+    pub fn advance(&mut self) {
+        self.vertices.remove(0);
+        self.vertices.push(HashMap::new());
+        self.edges.remove(0);
+        self.edges.push(HashMap::new());
+        self.parked.values_mut().for_each(|(_, time)| *time = time.saturating_sub(1));
+    }
+
+    /* Reserve the goal of the agent from the step `time` onward, past the end of the window */
+    pub fn park(&mut self, time: u32, goal: P, id: u32) {
+        self.parked.insert(goal, (id, time));
+    }
+
+    /* Agent parked on the tile at the end of the step `time`, the step may lie past the window */
+    pub fn get_parked(&self, time: u32, pos: P) -> Option<u32> {
+        self.parked.get(&pos).filter(|(_, since)| time >= *since).map(|(id, _)| *id)
+    }
+
+    pub fn set_yielding(&mut self, id: u32, is_yielding: bool) {
+        if is_yielding {
+            self.yielding.insert(id);
+        } else {
+            self.yielding.remove(&id);
+        }
+    }
+
+    pub fn is_yielding(&self, id: u32) -> bool {
+        self.yielding.contains(&id)
+    }
+
+    /* Will give the yielding agents displaced since the last call by increasing ids,
+    they have to replan around the reservations that took their tile */
+    pub fn take_displaced(&mut self) -> Vec<u32> {
+        let mut displaced = std::mem::take(&mut self.displaced);
+        displaced.sort_unstable();
+        displaced.dedup();
+        displaced
+    }
+
+    /* Reserve the move of the agent from `from` to `to` during the step `time`,
+    a wait only reserves the tile */
+    pub fn reserve(&mut self, time: u32, from: P, to: P, id: u32) {
+        if let Some(previous) = self.vertices[time as usize].insert(to, id) {
+            if previous != id && self.yielding.contains(&previous) {
+                self.displaced.push(previous);
+            }
+        }
+
+        if from != to {
+            self.edges[time as usize].insert((from, to), id);
+        }
+    }
+
+    /* Agent occupying the tile at the end of the step `time` */
+    pub fn get(&self, time: u32, pos: P) -> Option<u32> {
+        self.vertices[time as usize].get(&pos).copied()
+    }
+
+    /* Agents occupying the tile at any step of the window or parked on it */
+    pub fn get_occupants(&self, pos: P) -> Vec<u32> {
+        let mut occupants = self.vertices.iter()
+            .filter_map(|slice| slice.get(&pos).copied())
+            .chain(self.parked.get(&pos).map(|(id, _)| *id))
+            .collect::<Vec<u32>>();
+
+        occupants.sort_unstable();
+        occupants.dedup();
+        occupants
+    }
+
+    /* Agent traversing the edge from `from` to `to` during the step `time` */
+    pub fn get_edge(&self, time: u32, from: P, to: P) -> Option<u32> {
+        self.edges[time as usize].get(&(from, to)).copied()
+    }
+
+    pub fn is_vertex_free(&self, time: u32, pos: P, id: u32) -> bool {
+        self.is_free_for(self.get(time, pos), id) && self.is_free_for(self.get_parked(time, pos), id)
+    }
+
+    /* An edge is taken if another agent goes the opposite way (swap) or,
+    depending on the policy, crosses it on the other diagonal. Unlike the tiles, the edges
+    of a yielding agent hold for everyone: taking its tile displaces it, but nothing would
+    replan it away from a swap */
+    pub fn is_edge_free<G: Graph<Node = P>>(&self, graph: &G, time: u32, from: P, to: P, id: u32) -> bool {
+
+        if from == to {
+            return true;
+        }
+
+        let is_free = |a: P, b: P| self.get_edge(time, a, b).is_none_or(|other| other == id);
+
+        if !is_free(to, from) {
+            return false;
+        }
+
+        if self.diagonal_crossing == DiagonalCrossing::Forbid {
+            return graph.get_crossing_edges(from, to)
+                .into_iter()
+                .all(|(a, b)| is_free(a, b));
+        }
+
+        true
+    }
+
+    pub fn is_move_free<G: Graph<Node = P>>(&self, graph: &G, time: u32, from: P, to: P, id: u32) -> bool {
+        self.is_vertex_free(time, to, id) && self.is_edge_free(graph, time, from, to, id)
+    }
+
+    /* The tiles of a yielding agent only hold for the agents yielding as well */
+    fn is_free_for(&self, occupant: Option<u32>, id: u32) -> bool {
+        match occupant {
+            Some(other) => other == id || (self.yielding.contains(&other) && !self.yielding.contains(&id)),
+            None => true
+        }
+    }
+}
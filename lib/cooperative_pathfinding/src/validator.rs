@@ -0,0 +1,152 @@
+use std::fmt;
+use std::fmt::Debug;
+use pathfinding_core::{AgentTask, DiagonalCrossing, Graph, TimedPath};
+
+/* Rule broken by a set of timed paths, agents are given by their task id
+and times by their index in the paths, N is the node type of the graph */
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation<N = (u32, u32)> {
+    // The path doesn't start where the agent stands
+    WrongStart { agent: u32, pos: N, start: N },
+
+    // Two agents on the same tile at the same time
+    VertexConflict { agents: (u32, u32), pos: N, time: usize },
+
+    // Two agents swapping their tiles, or crossing the same diagonal when it is forbidden,
+    // between time - 1 and time
+    EdgeConflict { agents: (u32, u32), moves: ((N, N), (N, N)), time: usize },
+
+    // An agent out of the map or on a tile its cost profile forbids, like a wall or water for a walker
+    Obstacle { agent: u32, pos: N, time: usize },
+
+    // Two consecutive positions that aren't one move of the move set apart, or not linked by an edge
+    InvalidMove { agent: u32, from: N, to: N, time: usize },
+
+    // The path ends away from the goal, a missing path ends on the start
+    GoalNotReached { agent: u32, pos: N, goal: N }
+}
+
+impl<N: Debug> fmt::Display for Violation<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::WrongStart { agent, pos, start } =>
+                write!(f, "agent {} starts on {:?} instead of {:?}", agent, pos, start),
+            Violation::VertexConflict { agents, pos, time } =>
+                write!(f, "agents {} and {} are both on {:?} at time {}", agents.0, agents.1, pos, time),
+            Violation::EdgeConflict { agents, moves, time } =>
+                write!(f, "agents {} and {} cross with moves {:?} and {:?} at time {}", agents.0, agents.1, moves.0, moves.1, time),
+            Violation::Obstacle { agent, pos, time } =>
+                write!(f, "agent {} is on the obstacle {:?} at time {}", agent, pos, time),
+            Violation::InvalidMove { agent, from, to, time } =>
+                write!(f, "agent {} jumps from {:?} to {:?} at time {}", agent, from, to, time),
+            Violation::GoalNotReached { agent, pos, goal } =>
+                write!(f, "agent {} stops on {:?} instead of {:?}", agent, pos, goal)
+        }
+    }
+}
+
+/* Certifies the timed paths of any solver, agents having reached
+the end of their path keep standing on its last tile unless they vanish */
+#[derive(Debug, Clone, Default)]
+pub struct PlanValidator {
+    pub diagonal_crossing: DiagonalCrossing,
+
+    // Agents leave the map at the end of their path, as in a solution with agents_vanish
+    pub agents_vanish: bool
+}
+
+impl PlanValidator {
+
+    pub fn new() -> PlanValidator {
+        PlanValidator::default()
+    }
+
+    /* Will return every violation of the paths, given in the same order as the tasks */
+    pub fn validate<G: Graph>(&self, map: &G, tasks: &[AgentTask<G::Node>], paths: &[TimedPath<G::Node>]) -> Vec<Violation<G::Node>> {
+
+        let mut violations = Vec::<Violation<G::Node>>::new();
+
+        // A missing path leaves the agent on its start
+        let paths = tasks.iter()
+            .enumerate()
+            .map(|(i, task)| match paths.get(i) {
+                Some(path) if !path.is_empty() => path.clone(),
+                _ => vec![task.start]
+            })
+            .collect::<Vec<TimedPath<G::Node>>>();
+
+        for (task, path) in tasks.iter().zip(paths.iter()) {
+
+            if path[0] != task.start {
+                violations.push(Violation::WrongStart { agent: task.id, pos: path[0], start: task.start });
+            }
+
+            for (time, pos) in path.iter().enumerate() {
+                if !map.contains(*pos) || map.is_obstacle_for(*pos, &task.profile) {
+                    violations.push(Violation::Obstacle { agent: task.id, pos: *pos, time });
+                }
+            }
+
+            for (time, step) in path.windows(2).enumerate() {
+
+                // Moving onto an obstacle is already reported
+                if !map.contains(step[1]) || map.is_obstacle_for(step[1], &task.profile) {
+                    continue;
+                }
+
+                // Waiting is always allowed
+                let is_move = step[0] == step[1] || (map.contains(step[0]) && map.get_neighbors_for(step[0], &task.profile)
+                    .iter()
+                    .any(|(next, _)| *next == step[1]));
+
+                if !is_move {
+                    violations.push(Violation::InvalidMove { agent: task.id, from: step[0], to: step[1], time: time + 1 });
+                }
+            }
+
+            let last = path[path.len() - 1];
+            if last != task.goal {
+                violations.push(Violation::GoalNotReached { agent: task.id, pos: last, goal: task.goal });
+            }
+        }
+
+        let makespan = paths.iter().map(|path| path.len()).max().unwrap_or(0);
+        let position = |agent: usize, time: usize| {
+            let path = &paths[agent];
+            path[time.min(path.len() - 1)]
+        };
+        let is_present = |agent: usize, time: usize| !self.agents_vanish || time < paths[agent].len();
+
+        for time in 0..makespan {
+            for a in 0..tasks.len() {
+                for b in a + 1..tasks.len() {
+
+                    if !is_present(a, time) || !is_present(b, time) {
+                        continue;
+                    }
+
+                    let agents = (tasks[a].id, tasks[b].id);
+                    let a_pos = position(a, time);
+                    let b_pos = position(b, time);
+
+                    if a_pos == b_pos {
+                        violations.push(Violation::VertexConflict { agents, pos: a_pos, time });
+                    }
+
+                    if time == 0 {
+                        continue;
+                    }
+
+                    let a_move = (position(a, time - 1), a_pos);
+                    let b_move = (position(b, time - 1), b_pos);
+
+                    if map.is_edge_conflict(a_move, b_move, self.diagonal_crossing) {
+                        violations.push(Violation::EdgeConflict { agents, moves: (a_move, b_move), time });
+                    }
+                }
+            }
+        }
+
+        violations
+    }
+}
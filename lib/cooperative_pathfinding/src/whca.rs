@@ -0,0 +1,439 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Instant;
+use rayon::ThreadPoolBuilder;
+use pathfinding_core::{AgentTask, Graph, GridMap, Heuristic, MapfSolver, Octile, PathfindingError, Solution, TimedPath};
+
+use crate::{AgentStatus, AgentStore, CoordinationEvent, GoalParking, Node, PlannerConfig, SpaceTimeMap, WorldMap};
+
+const DEFAULT_MAX_STEPS: u32 = 1000;
+
+/* Windowed Hierarchical Cooperative A*, agents are moved one step at a time
+and replan their next window once per replan interval of the config */
+#[derive(Debug, Clone)]
+pub struct WhcaSolver {
+    // Bound on the simulated steps, agents still walking after it are left where they are
+    pub max_steps: u32,
+
+    pub config: PlannerConfig,
+
+    // Window of the agents planning further or closer than the config, by task id
+    pub window_sizes: HashMap<u32, u32>,
+
+    // Threads computing the heuristics of the agents, 0 uses one per core
+    pub threads: usize,
+
+    // Coordination troubles met during the last solve, agents given by task id
+    pub events: Vec<CoordinationEvent>,
+
+    // Guides the Reverse Resumable A* of every goal on grid maps
+    pub heuristic: Arc<dyn Heuristic>
+}
+
+impl Default for WhcaSolver {
+    fn default() -> Self {
+        WhcaSolver::new()
+    }
+}
+
+impl WhcaSolver {
+
+    pub fn new() -> WhcaSolver {
+        WhcaSolver {
+            max_steps: DEFAULT_MAX_STEPS,
+            config: PlannerConfig::default(),
+            window_sizes: HashMap::new(),
+            threads: 0,
+            events: Vec::new(),
+            heuristic: Arc::new(Octile)
+        }
+    }
+
+    /* Will move every agent by one step, replanning their windows if needed, and return the coordination
+    troubles found on the way, agents given by their id in the store. The heuristics run on the current
+    rayon thread pool, then the reservations are made one agent at a time */
+    pub fn step<G: Graph + Sync>(&self, graph: &G, space_time_map: &mut SpaceTimeMap<G::Node>, agents: &mut AgentStore<G::Node>, steps: u32) -> Result<Vec<CoordinationEvent>, PathfindingError<G::Node>>
+    where G::Node: Send + Sync {
+
+        let replan_interval = self.config.get_replan_interval();
+        let mut replanning = Vec::<u32>::new();
+        let positions = agents.iter().map(|agent| agent.current_node.pos).collect::<Vec<G::Node>>();
+        let mut events = Vec::new();
+
+        // A vanishing agent leaves the map as soon as it stands on its goal
+        for agent in agents.iter_mut() {
+            if agent.goal_parking == GoalParking::Vanish && !agent.has_vanished && agent.current_node == agent.goal {
+                agent.has_vanished = true;
+                agent.portion_path.clear();
+                space_time_map.release(agent.get_id());
+            }
+        }
+
+        // Once per replan interval, at its own offset, each agent releases its reservations
+        // and plans its next window from where it stands, the first window is planned by all
+        let mut stalled = Vec::<u32>::new();
+
+        for (id, agent) in agents.get_ids().zip(agents.iter()).filter(|(_, agent)| !agent.has_vanished) {
+            if agent.portion_path.is_empty() || steps % replan_interval == agent.replan_offset % replan_interval {
+                if self.config.is_stalled(agent.stalled_windows) {
+                    let (step, windows) = (steps, agent.stalled_windows);
+
+                    events.push(if agent.window_moves > 0 {
+                        CoordinationEvent::Livelock { step, agent: id, windows }
+                    } else {
+                        CoordinationEvent::Stall { step, agent: id, windows }
+                    });
+                    stalled.push(id);
+                }
+                replanning.push(id);
+            }
+        }
+
+        let scheduled = replanning.clone();
+
+        // A stalled agent may be blocked by the reservations of agents planned before it, so the agents
+        // in its way replan after it, only the parked agents keep going first
+        for id in stalled {
+            for other in self.find_agents_in_the_way(graph, space_time_map, agents, id) {
+                space_time_map.release(other);
+
+                if !replanning.contains(&other) {
+                    replanning.push(other);
+                }
+            }
+            space_time_map.release(id);
+        }
+
+        // The agents of a deadlock replan together, without the reservations keeping them waiting
+        let deadlocks = self.find_deadlocks(graph, agents);
+
+        for id in deadlocks.iter().flatten() {
+            space_time_map.release(*id);
+
+            if !replanning.contains(id) {
+                replanning.push(*id);
+            }
+        }
+
+        let mut replanning = replanning.iter()
+            .map(|id| agents.get(*id).unwrap().get_status(graph))
+            .collect::<Vec<AgentStatus>>();
+
+        self.config.priority.sort(&mut replanning, steps);
+        replanning.sort_by_key(|status| Reverse(if self.config.is_stalled(status.stalled_windows) { status.stalled_windows } else { 0 }));
+
+        // The agent of each deadlock coming last in the priority order backs off,
+        // it replans after the others so they plan through its tile
+        let backing_off = deadlocks.iter()
+            .map(|cycle| replanning.iter().rev().find(|status| cycle.contains(&status.id)).unwrap().id)
+            .collect::<Vec<u32>>();
+
+        for (cycle, backing_off) in deadlocks.iter().zip(backing_off.iter()) {
+            events.push(CoordinationEvent::Deadlock { step: steps, agents: cycle.clone(), backing_off: *backing_off });
+        }
+
+        // Parked agents keep their goal reserved, whatever the others' priority,
+        // then come the deadlocks
+        replanning.sort_by_key(|status| (
+            !status.is_parked,
+            !deadlocks.iter().flatten().any(|id| *id == status.id),
+            backing_off.contains(&status.id)
+        ));
+
+        let mut ids = replanning.iter().map(|status| status.id).collect::<Vec<u32>>();
+        agents.expand_heuristics(graph, &ids);
+
+        // An agent left without any window by the agents planned before it, a deadlock member
+        // with nowhere to back off included, is planned first and every agent replans after it.
+        // The last agent blocked goes first, until there were as many restarts as agents
+        let mut blocked = Vec::<u32>::new();
+        let mut restarts = 0;
+
+        while let Err(error) = self.replan_agents(graph, space_time_map, agents, &ids) {
+
+            let id = match error {
+                PathfindingError::WindowExhausted { agent, .. } if restarts < agents.len() => agent,
+                _ => return Err(error)
+            };
+
+            events.push(CoordinationEvent::Blocked { step: steps, agent: id });
+            blocked.retain(|other| *other != id);
+            blocked.insert(0, id);
+            restarts += 1;
+            space_time_map.clear();
+
+            // Parked agents still keep their goal reserved, the ones that weren't due to replan included
+            let parked = agents.iter()
+                .filter(|agent| !agent.has_vanished && agent.get_status(graph).is_parked)
+                .map(|agent| agent.get_id())
+                .collect::<Vec<u32>>();
+
+            let others = ids.iter().copied().chain(agents.get_ids())
+                .filter(|id| !agents.get(*id).unwrap().has_vanished);
+
+            ids = parked.into_iter().chain(blocked.iter().copied()).chain(others)
+                .fold(Vec::new(), |mut ids, id| {
+                    if !ids.contains(&id) {
+                        ids.push(id);
+                    }
+                    ids
+                });
+        }
+
+        // Progress is counted once per window planned on schedule, whatever the replannings of the step
+        for id in scheduled {
+            let (agent, heuristic, _) = agents.get_mut_for_planning(id).unwrap();
+            agent.update_progress(graph, heuristic);
+        }
+
+        for (agent, position) in agents.iter_mut().zip(positions.iter()).filter(|(agent, _)| !agent.has_vanished) {
+            agent.current_node = agent.portion_path.pop().unwrap();
+
+            if agent.current_node.pos == *position && agent.current_node != agent.goal {
+                agent.delay += 1;
+                agent.waiting_steps += 1;
+            } else {
+                agent.waiting_steps = 0;
+            }
+
+            if agent.current_node.pos != *position {
+                agent.window_moves += 1;
+            }
+        }
+
+        // Index 0 always holds the reservations of the next step
+        space_time_map.advance();
+        Ok(events)
+    }
+
+    /* Will give the agents standing or planning to stand on the best route of the agent over its window,
+    the parked agents left out */
+    fn find_agents_in_the_way<G: Graph>(&self, graph: &G, space_time_map: &SpaceTimeMap<G::Node>, agents: &mut AgentStore<G::Node>, id: u32) -> Vec<u32> {
+
+        let (agent, heuristic, _) = agents.get_mut_for_planning(id).unwrap();
+        let route = agent.get_route(graph, heuristic);
+
+        let mut in_the_way = route.iter()
+            .flat_map(|pos| space_time_map.get_occupants(*pos))
+            .chain(agents.iter().filter(|other| !other.has_vanished && route.contains(&other.current_node.pos)).map(|other| other.get_id()))
+            .filter(|other| *other != id && !agents.get(*other).unwrap().get_status(graph).is_parked)
+            .collect::<Vec<u32>>();
+
+        in_the_way.sort_unstable();
+        in_the_way.dedup();
+        in_the_way
+    }
+
+    /* Will look for the cycles of agents each waiting for the tile of the next one,
+    following the best move of each agent toward its goal */
+    fn find_deadlocks<G: Graph>(&self, graph: &G, agents: &mut AgentStore<G::Node>) -> Vec<Vec<u32>> {
+
+        let occupants = agents.iter()
+            .filter(|agent| !agent.has_vanished)
+            .map(|agent| (agent.current_node.pos, agent.get_id()))
+            .collect::<HashMap<G::Node, u32>>();
+
+        let mut waits_for = HashMap::<u32, u32>::new();
+
+        for id in agents.get_ids() {
+            let (agent, heuristic, _) = agents.get_mut_for_planning(id).unwrap();
+
+            if agent.has_vanished || !self.config.is_deadlocked(agent.waiting_steps) {
+                continue;
+            }
+
+            if let Some(other) = agent.get_next_node(graph, heuristic).and_then(|next| occupants.get(&next)) {
+                waits_for.insert(id, *other);
+            }
+        }
+
+        // Each agent waits for one other at most, so following the waits from an agent
+        // either leaves the graph or ends in a cycle
+        let mut deadlocks = Vec::new();
+        let mut visited = HashMap::<u32, u32>::new();
+
+        for start in agents.get_ids() {
+            let mut id = start;
+
+            while let Some(other) = waits_for.get(&id) {
+                if visited.contains_key(&id) {
+                    break;
+                }
+                visited.insert(id, start);
+                id = *other;
+            }
+
+            // The walk from start came back on itself, the agents from id onward form the cycle
+            if visited.get(&id) == Some(&start) && waits_for.contains_key(&id) {
+                let mut cycle = vec![id];
+                let mut other = waits_for[&id];
+
+                while other != id {
+                    cycle.push(other);
+                    other = waits_for[&other];
+                }
+                deadlocks.push(cycle);
+            }
+        }
+
+        deadlocks
+    }
+
+    fn replan_agents<G: Graph>(&self, graph: &G, space_time_map: &mut SpaceTimeMap<G::Node>, agents: &mut AgentStore<G::Node>, ids: &[u32]) -> Result<(), PathfindingError<G::Node>> {
+        for id in ids {
+            self.replan(graph, space_time_map, agents, *id)?;
+        }
+        Ok(())
+    }
+
+    /* Will replan the agent, the yielding agents whose tile it takes step aside right away.
+    One that can't is kept on its goal for this step and the agent replans around it */
+    fn replan<G: Graph>(&self, graph: &G, space_time_map: &mut SpaceTimeMap<G::Node>, agents: &mut AgentStore<G::Node>, id: u32) -> Result<(), PathfindingError<G::Node>> {
+
+        loop {
+            let (agent, heuristic, state) = agents.get_mut_for_planning(id).unwrap();
+            agent.replan(graph, space_time_map, heuristic, state)?;
+
+            // Yielding agents respect every reservation, so they never displace anyone
+            let mut blocking = Vec::new();
+
+            for other in space_time_map.take_displaced() {
+                let (parked, heuristic, state) = agents.get_mut_for_planning(other).unwrap();
+
+                if parked.replan(graph, space_time_map, heuristic, state).is_err() {
+                    blocking.push(other);
+                }
+            }
+
+            if blocking.is_empty() {
+                return Ok(());
+            }
+
+            space_time_map.release(id);
+
+            for other in blocking {
+                let (parked, heuristic, state) = agents.get_mut_for_planning(other).unwrap();
+                parked.replan(graph, space_time_map, heuristic, state)?;
+                space_time_map.set_yielding(other, false);
+            }
+        }
+    }
+
+    /* Will plan every task on any graph, paths are given in the same order as the tasks */
+    pub fn solve_graph<G: Graph + Sync>(&mut self, graph: &G, tasks: &[AgentTask<G::Node>]) -> Result<Solution<G::Node>, PathfindingError<G::Node>>
+    where G::Node: Send + Sync {
+
+        let start_time = Instant::now();
+        let mut agents = AgentStore::new();
+
+        // Agents are planned by increasing ids starting from 1
+        for (i, task) in tasks.iter().enumerate() {
+
+            for node in [task.start, task.goal].iter() {
+                if !graph.contains(*node) {
+                    return Err(PathfindingError::OutOfBounds(*node));
+                }
+            }
+
+            if graph.is_obstacle_for(task.start, &task.profile) {
+                return Err(PathfindingError::StartBlocked(task.start));
+            }
+
+            let config = PlannerConfig {
+                window_size: *self.window_sizes.get(&task.id).unwrap_or(&self.config.window_size),
+                ..self.config
+            };
+
+            let id = agents.add(&task.id.to_string(), Node::new(task.start, 0), Node::new(task.goal, 0), task.profile, &config);
+            agents.get_mut(id).unwrap().replan_offset = self.config.get_replan_offset(i);
+        }
+
+        // Vanished agents leave their goal to the next one
+        if self.config.goal_parking != GoalParking::Vanish {
+            AgentTask::check_goals(tasks)?;
+        }
+
+        // The reservations span the longest window
+        let window_size = agents.iter().map(|agent| agent.window_size).max().unwrap_or(0);
+        let mut space_time_map = SpaceTimeMap::new(window_size);
+        space_time_map.diagonal_crossing = self.config.diagonal_crossing;
+
+        let mut paths = tasks.iter()
+            .map(|task| vec![task.start])
+            .collect::<Vec<TimedPath<G::Node>>>();
+
+        let mut tick_times = Vec::new();
+        let mut events = Vec::new();
+
+        let mut run = || {
+            let mut steps = 0;
+
+            while steps < self.max_steps && tasks.iter().zip(paths.iter()).any(|(task, path)| path.last() != Some(&task.goal)) {
+
+                let tick_time = Instant::now();
+                events.extend(self.step(graph, &mut space_time_map, &mut agents, steps)?);
+                tick_times.push(tick_time.elapsed());
+
+                for (path, agent) in paths.iter_mut().zip(agents.iter()) {
+                    path.push(agent.current_node.pos);
+                }
+
+                steps += 1;
+            }
+
+            Ok(())
+        };
+
+        // Without a pool of its own, the solver shares the global one
+        match ThreadPoolBuilder::new().num_threads(self.threads).build() {
+            Ok(pool) => pool.install(run)?,
+            Err(_) => run()?
+        }
+
+        // Agents resting on their goal are not moving anymore, their path stops at their last arrival,
+        // the others keep the steps they waited until the end
+        for (path, task) in paths.iter_mut().zip(tasks.iter()) {
+            while path.last() == Some(&task.goal) && path.len() > 1 && path[path.len() - 1] == path[path.len() - 2] {
+                path.pop();
+            }
+        }
+
+        // The agents of the store are numbered from 1 in the order of the tasks
+        self.events = events.iter()
+            .map(|event| event.rename_agents(|id| tasks[id as usize - 1].id))
+            .collect();
+
+        let mut solution = Solution {
+            paths,
+            agents_vanish: self.config.goal_parking == GoalParking::Vanish,
+            diagonal_crossing: self.config.diagonal_crossing,
+            ..Default::default()
+        };
+        solution.stats.nodes_expanded_per_agent = agents.iter().map(|agent| agent.nodes_expanded).collect();
+        solution.stats.nodes_expanded = solution.stats.nodes_expanded_per_agent.iter().sum();
+        solution.stats.planning_time = start_time.elapsed();
+        solution.stats.tick_times = tick_times;
+        solution.stats.events = self.events.iter().map(|event| event.to_string()).collect();
+        Ok(solution)
+    }
+}
+
+impl MapfSolver for WhcaSolver {
+
+    fn name(&self) -> &str {
+        "whca"
+    }
+
+    fn solve(&mut self, map: &GridMap, tasks: &[AgentTask]) -> Result<Solution, PathfindingError> {
+
+        for task in tasks {
+            map.check_task(task)?;
+        }
+
+        let mut world_map = WorldMap::from(map);
+        world_map.heuristic = self.heuristic.clone();
+
+        self.solve_graph(&world_map, tasks)
+    }
+}
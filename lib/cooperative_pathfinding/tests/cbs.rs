@@ -0,0 +1,41 @@
+use cooperative_pathfinding::{ConflictBasedSearch, PlanValidator, WhcaSolver, WorldMap};
+use pathfinding_core::{AgentTask, GridMap, MapfSolver, Metrics, MoveSet};
+
+mod common;
+use common::get_corridor;
+
+fn get_sum_of_costs(solver: &mut dyn MapfSolver, map: &GridMap, tasks: &[AgentTask]) -> usize {
+    let solution = solver.solve(map, tasks).unwrap();
+    let violations = PlanValidator::new().validate(&WorldMap::from(map), tasks, &solution.paths);
+    assert!(violations.is_empty(), "{} {:?}", solver.name(), violations);
+
+    Metrics::new(solver.name(), map, tasks, &solution).sum_of_costs
+}
+
+#[test]
+fn conflict_based_search_is_optimal() {
+    // Two rows of four tiles, the second agent only has one step to make
+    let mut map = GridMap::new(vec![1; 4 * 2], 4, 2);
+    map.move_set = MoveSet::FourWay;
+
+    let tasks = vec![
+        AgentTask::new(0, (2, 0), (0, 1)),
+        AgentTask::new(1, (0, 1), (1, 1))
+    ];
+
+    // Both agents can take a shortest path, 3 + 1 steps, if the first one goes through the top row.
+    // Planned first, it may as well go through the bottom row and make the second one wait for it
+    assert_eq!(get_sum_of_costs(&mut ConflictBasedSearch::new(), &map, &tasks), 4);
+    assert!(get_sum_of_costs(&mut WhcaSolver::new(), &map, &tasks) > 4);
+
+    // Agents crossing in a corridor, the pocket is next to the start of the first one.
+    // The second one walks straight to its goal in 4 steps, the first one steps into the pocket
+    // and back, waiting there one step for the second one to pass, 4 + 2 + 1 steps
+    let map = get_corridor(5, &[1]);
+    let tasks = vec![
+        AgentTask::new(0, (0, 0), (4, 0)),
+        AgentTask::new(1, (4, 0), (0, 0))
+    ];
+
+    assert_eq!(get_sum_of_costs(&mut ConflictBasedSearch::new(), &map, &tasks), 11);
+}
@@ -0,0 +1,16 @@
+use pathfinding_core::{GridMap, MoveSet};
+
+/* One tile wide corridor along the first row, with a pocket under each of the given columns
+to step aside in, moves are four-way so that agents can't cut around each other */
+pub fn get_corridor(length: u32, pockets: &[u32]) -> GridMap {
+    let mut data = vec![u32::MAX; (length * 2) as usize];
+    data[..length as usize].iter_mut().for_each(|cost| *cost = 1);
+
+    for x in pockets.iter() {
+        data[(length + x) as usize] = 1;
+    }
+
+    let mut map = GridMap::new(data, length, 2);
+    map.move_set = MoveSet::FourWay;
+    map
+}
@@ -0,0 +1,76 @@
+use a_star_pathfinding::AStarSolver;
+use cooperative_pathfinding::{ConflictBasedSearch, GoalParking, WhcaSolver};
+use pathfinding_core::{AgentTask, GridMap, MapfSolver, PathfindingError};
+
+const O: u32 = u32::MAX;
+
+/* Room with a walled off corner at its right */
+fn get_map() -> GridMap {
+    let data = vec![
+        1, 1, 1, O, 1,
+        1, 1, 1, O, O,
+        1, O, 1, 1, 1
+    ];
+
+    GridMap::new(data, 5, 3)
+}
+
+fn get_solvers() -> Vec<Box<dyn MapfSolver>> {
+    vec![
+        Box::new(AStarSolver::new()),
+        Box::new(WhcaSolver::new()),
+        Box::new(ConflictBasedSearch::new())
+    ]
+}
+
+fn assert_error(solvers: Vec<Box<dyn MapfSolver>>, tasks: &[AgentTask], expected: PathfindingError) {
+    let map = get_map();
+
+    for mut solver in solvers {
+        assert_eq!(solver.solve(&map, tasks).unwrap_err(), expected, "{}", solver.name());
+    }
+}
+
+#[test]
+fn positions_out_of_the_map_are_rejected() {
+    let tasks = vec![AgentTask::new(0, (0, 0), (2, 2)), AgentTask::new(1, (1, 0), (5, 1))];
+    assert_error(get_solvers(), &tasks, PathfindingError::OutOfBounds((5, 1)));
+
+    let tasks = vec![AgentTask::new(0, (0, 3), (2, 2))];
+    assert_error(get_solvers(), &tasks, PathfindingError::OutOfBounds((0, 3)));
+}
+
+#[test]
+fn starts_on_an_obstacle_are_rejected() {
+    let tasks = vec![AgentTask::new(0, (1, 2), (0, 0))];
+    assert_error(get_solvers(), &tasks, PathfindingError::StartBlocked((1, 2)));
+}
+
+#[test]
+fn unreachable_goals_are_rejected() {
+    // On an obstacle
+    let tasks = vec![AgentTask::new(0, (0, 0), (3, 0))];
+    assert_error(get_solvers(), &tasks, PathfindingError::GoalUnreachable { start: (0, 0), goal: (3, 0) });
+
+    // In the walled off corner
+    let tasks = vec![AgentTask::new(0, (0, 0), (4, 0))];
+    assert_error(get_solvers(), &tasks, PathfindingError::GoalUnreachable { start: (0, 0), goal: (4, 0) });
+}
+
+#[test]
+fn shared_goals_are_rejected() {
+    let tasks = vec![
+        AgentTask::new(0, (0, 0), (4, 2)),
+        AgentTask::new(1, (0, 1), (2, 0)),
+        AgentTask::new(2, (2, 2), (4, 2))
+    ];
+
+    // A* plans each agent on its own and doesn't care
+    let solvers: Vec<Box<dyn MapfSolver>> = vec![Box::new(WhcaSolver::new()), Box::new(ConflictBasedSearch::new())];
+    assert_error(solvers, &tasks, PathfindingError::DuplicateGoal { agents: (0, 2), goal: (4, 2) });
+
+    // Agents vanishing on their goal leave it to the next one
+    let mut solver = WhcaSolver::new();
+    solver.config.goal_parking = GoalParking::Vanish;
+    assert!(solver.solve(&get_map(), &tasks).is_ok());
+}
@@ -0,0 +1,41 @@
+use cooperative_pathfinding::{PlanValidator, Violation, WhcaSolver};
+use pathfinding_core::{AgentTask, NavGraph};
+
+/* Six waypoints on a ring, with a one way shortcut across it from 0 to 3 */
+fn get_graph() -> NavGraph {
+    let mut graph = NavGraph::new();
+
+    for pos in [(0, 2), (1, 0), (3, 0), (4, 2), (3, 4), (1, 4)].iter() {
+        graph.add_node(*pos);
+    }
+
+    // Edges cost at least the straight distance between their waypoints
+    for (a, b, cost) in [(0, 1, 224), (1, 2, 200), (2, 3, 224), (3, 4, 224), (4, 5, 200), (5, 0, 224)].iter() {
+        graph.connect(*a, *b, *cost);
+    }
+    graph.add_edge(0, 3, 400);
+
+    graph
+}
+
+#[test]
+fn waypoint_graphs_are_solved() {
+    let graph = get_graph();
+    let tasks = vec![
+        AgentTask::new(0, 0, 3),
+        AgentTask::new(1, 3, 0)
+    ];
+
+    let solution = WhcaSolver::new().solve_graph(&graph, &tasks).unwrap();
+    let violations = PlanValidator::new().validate(&graph, &tasks, &solution.paths);
+    assert!(violations.is_empty(), "{:?}", violations);
+
+    // The first agent takes the shortcut, the second one has to go around the ring
+    assert_eq!(solution.paths[0], vec![0, 3]);
+    assert!(solution.paths[1].len() >= 4, "{:?}", solution.paths[1]);
+
+    // Taking the shortcut the wrong way is not a move
+    let paths = vec![vec![0, 1, 2, 3], vec![3, 0]];
+    let violations = PlanValidator::new().validate(&graph, &tasks, &paths);
+    assert_eq!(violations, vec![Violation::InvalidMove { agent: 1, from: 3, to: 0, time: 1 }]);
+}
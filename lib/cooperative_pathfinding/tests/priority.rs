@@ -0,0 +1,76 @@
+use cooperative_pathfinding::{AgentStatus, AgentStore, Node, PlannerConfig, PriorityStrategy, SpaceTimeMap, WhcaSolver, WorldMap};
+use pathfinding_core::{CostProfile, GridMap};
+
+fn get_status(id: u32, distance: u32, delay: u32) -> AgentStatus {
+    AgentStatus {
+        id,
+        distance,
+        is_parked: false,
+        delay,
+        stalled_windows: 0
+    }
+}
+
+/* Will sort the agents given in any order and return their ids by decreasing priority */
+fn sort(strategy: PriorityStrategy, agents: &[AgentStatus], steps: u32) -> Vec<u32> {
+    let mut agents = agents.to_vec();
+    agents.reverse();
+    strategy.sort(&mut agents, steps);
+    agents.iter().map(|agent| agent.id).collect()
+}
+
+#[test]
+fn random_orders_are_seeded() {
+    let agents = (1..=8).map(|id| get_status(id, 0, 0)).collect::<Vec<AgentStatus>>();
+    let seeded = |seed| PriorityStrategy::Random { seed };
+
+    // The same seed gives the same order of the same agents on the same step
+    assert_eq!(sort(seeded(7), &agents, 3), sort(seeded(7), &agents, 3));
+    let mut order = sort(seeded(7), &agents, 3);
+    order.sort_unstable();
+    assert_eq!(order, (1..=8).collect::<Vec<u32>>());
+
+    // Other seeds and other steps give other orders
+    assert_ne!(sort(seeded(7), &agents, 3), sort(seeded(8), &agents, 3));
+    assert_ne!(sort(seeded(7), &agents, 3), sort(seeded(7), &agents, 4));
+}
+
+#[test]
+fn agents_are_sorted_by_distance_or_delay() {
+    let agents = [get_status(1, 300, 2), get_status(2, 500, 0), get_status(3, 100, 4), get_status(4, 500, 4)];
+
+    assert_eq!(sort(PriorityStrategy::Fixed, &agents, 0), vec![1, 2, 3, 4]);
+
+    // Ties are kept by increasing ids
+    assert_eq!(sort(PriorityStrategy::FarthestFirst, &agents, 0), vec![2, 4, 1, 3]);
+    assert_eq!(sort(PriorityStrategy::MostDelayedFirst, &agents, 0), vec![3, 4, 1, 2]);
+}
+
+#[test]
+fn stalled_agents_are_planned_first() {
+    // Both agents want the center of an open room on the first step
+    let map = WorldMap::from(&GridMap::new(vec![1; 3 * 3], 3, 3));
+    let tasks = [((0, 1), (2, 1)), ((1, 0), (1, 2))];
+
+    let get_first_moves = |stalled_windows: u32| {
+        let mut solver = WhcaSolver::new();
+        solver.config = PlannerConfig { staggered_replanning: false, ..PlannerConfig::new(4, 2) };
+
+        let mut agents = AgentStore::new();
+        let mut space_time_map = SpaceTimeMap::new(solver.config.window_size);
+
+        for (start, goal) in tasks.iter() {
+            agents.add("agent", Node::new(*start, 0), Node::new(*goal, 0), CostProfile::default(), &solver.config);
+        }
+        agents.get_mut(2).unwrap().stalled_windows = stalled_windows;
+
+        solver.step(&map, &mut space_time_map, &mut agents, 0).unwrap();
+        agents.iter().map(|agent| agent.current_node.pos).collect::<Vec<(u32, u32)>>()
+    };
+
+    // By id, the first agent gets the center
+    assert_eq!(get_first_moves(0)[0], (1, 1));
+
+    // Stalled for as many windows as the config allows, the second one does
+    assert_eq!(get_first_moves(PlannerConfig::default().stall_windows)[1], (1, 1));
+}
@@ -0,0 +1,106 @@
+use cooperative_pathfinding::{DistanceStore, PagedSearchState, ReverseResumableAStar, SearchState, WorldMap};
+use pathfinding_core::{CostProfile, Graph, GridMap, MoveSet};
+
+/* Room with a wall splitting it, except for a one tile gap, and a costly tile */
+fn get_map(move_set: MoveSet) -> WorldMap {
+    let mut data = vec![1; 9 * 7];
+    for y in 0..7 {
+        if y != 3 {
+            data[y * 9 + 4] = u32::MAX;
+        }
+    }
+    data[2 * 9 + 6] = 5;
+
+    let mut map = GridMap::new(data, 9, 7);
+    map.move_set = move_set;
+    WorldMap::from(&map)
+}
+
+#[test]
+fn shared_search_gives_exact_distances() {
+    for move_set in [MoveSet::FourWay, MoveSet::EightWay].iter() {
+        let map = get_map(*move_set);
+        let goal = (8, 6);
+
+        // One search shared by every query, resumed lazily
+        let mut shared = ReverseResumableAStar::new(goal, (0, 0), CostProfile::default());
+
+        for index in 0..map.get_node_count() {
+            let pos = map.get_node(index);
+
+            // A search aimed at the queried node expands it as soon as possible
+            let mut single = ReverseResumableAStar::new(goal, pos, CostProfile::default());
+            let expected = single.get_distance(&map, pos);
+
+            assert_eq!(shared.get_distance(&map, pos), expected, "{:?} {:?}", move_set, pos);
+            assert_eq!(expected.is_none(), map.is_obstacle(pos));
+        }
+
+        // Answering again only reads the expanded nodes
+        let nodes_expanded = shared.nodes_expanded;
+        assert!(shared.get_distance(&map, (0, 0)).is_some());
+        assert_eq!(shared.nodes_expanded, nodes_expanded);
+    }
+}
+
+#[test]
+fn paged_search_matches_the_dense_one() {
+    let map = get_map(MoveSet::EightWay);
+    let goal = (0, 6);
+
+    let mut dense = ReverseResumableAStar::with_state(goal, (8, 0), CostProfile::default(), SearchState::new());
+    let mut paged = ReverseResumableAStar::new(goal, (8, 0), CostProfile::default());
+
+    for index in (0..map.get_node_count()).rev() {
+        let pos = map.get_node(index);
+        assert_eq!(paged.get_distance(&map, pos), dense.get_distance(&map, pos), "{:?}", pos);
+    }
+
+    assert_eq!(paged.nodes_expanded, dense.nodes_expanded);
+
+    // Nothing is allocated before a node is reached, and a reset forgets every page
+    let mut state = PagedSearchState::new();
+    state.reset(200);
+    assert_eq!(state.get_page_count(), 0);
+    assert_eq!(state.get_g_score(150), u32::MAX);
+
+    state.set_g_score(150, 4);
+    state.close(150);
+    state.close(3);
+    assert_eq!(state.get_page_count(), 1);
+    assert!(state.is_closed(150) && !state.is_closed(3));
+    assert_eq!(state.get_g_score(150), 4);
+
+    state.reset(200);
+    assert_eq!(state.get_page_count(), 0);
+    assert!(!state.is_closed(150));
+}
+
+#[test]
+fn search_state_forgets_on_reset() {
+    let mut state = SearchState::new();
+    state.reset(4);
+
+    state.set(0, 0, 0);
+    state.set(2, 7, 0);
+    state.close(2);
+
+    assert_eq!(state.get_parent(0), None);
+    assert_eq!(state.get_parent(2), Some(0));
+    assert_eq!(state.get_g_score(2), 7);
+    assert!(state.is_closed(2));
+
+    // A smaller reset keeps the buffers, but nothing written before is left
+    state.reset(3);
+    assert_eq!(state.len(), 4);
+
+    for index in 0..4 {
+        assert_eq!(state.get_g_score(index), u32::MAX);
+        assert_eq!(state.get_parent(index), None);
+        assert!(!state.is_closed(index));
+    }
+
+    state.set(2, 3, 1);
+    assert!(!state.is_closed(2));
+    assert_eq!(state.get_parent(2), Some(1));
+}
@@ -0,0 +1,32 @@
+use cooperative_pathfinding::{SpaceTimeMap, WorldMap};
+use pathfinding_core::GridMap;
+
+#[test]
+fn released_agents_leave_the_others_reservations() {
+    let map = WorldMap::from(&GridMap::new(vec![1; 4 * 4], 4, 4));
+    let mut space_time_map = SpaceTimeMap::new(4);
+
+    // Agent 1 walks along the first row while agent 2 comes down the last column
+    for time in 0..3 {
+        space_time_map.reserve(time, (time, 0), (time + 1, 0), 1);
+        space_time_map.reserve(time, (3, time), (3, time + 1), 2);
+    }
+    space_time_map.reserve(3, (3, 0), (3, 0), 1);
+
+    space_time_map.release(1);
+
+    for time in 0..3 {
+        assert_eq!(space_time_map.get(time, (time + 1, 0)), None);
+        assert_eq!(space_time_map.get_edge(time, (time, 0), (time + 1, 0)), None);
+        assert!(space_time_map.is_move_free(&map, time, (time + 1, 0), (time, 0), 3));
+
+        assert_eq!(space_time_map.get(time, (3, time + 1)), Some(2));
+        assert_eq!(space_time_map.get_edge(time, (3, time), (3, time + 1)), Some(2));
+        assert!(!space_time_map.is_move_free(&map, time, (3, time + 1), (3, time), 3));
+    }
+    assert_eq!(space_time_map.get(3, (3, 0)), None);
+
+    // Releasing an agent without reservations changes nothing
+    space_time_map.release(1);
+    assert_eq!(space_time_map.get(2, (3, 3)), Some(2));
+}
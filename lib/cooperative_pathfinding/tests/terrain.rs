@@ -0,0 +1,106 @@
+use a_star_pathfinding::AStarSolver;
+use cooperative_pathfinding::{ConflictBasedSearch, PlanValidator, Violation, WhcaSolver, WorldMap};
+use pathfinding_core::movingai::parse_map;
+use pathfinding_core::{AgentClass, AgentTask, CostProfile, Graph, GridMap, MapfSolver, MoveSet, PathfindingError, Terrain, TerrainLayer, WATER_COST};
+
+/* A lake with an island in its middle, and a shore going around it */
+fn get_map() -> GridMap {
+    let x = WATER_COST;
+    let data = vec![
+        1, x, x, x, x, x, 1,
+        1, x, x, 1, x, x, 1,
+        1, x, x, x, x, x, 1,
+        1, 1, 1, 1, 1, 1, 1
+    ];
+
+    let mut map = GridMap::new(data, 7, 4);
+    map.move_set = MoveSet::FourWay;
+    map
+}
+
+fn get_solvers() -> Vec<Box<dyn MapfSolver>> {
+    vec![
+        Box::new(AStarSolver::new()),
+        Box::new(WhcaSolver::new()),
+        Box::new(ConflictBasedSearch::new())
+    ]
+}
+
+#[test]
+fn profiles_give_the_cost_of_each_terrain() {
+    assert_eq!(Terrain::from_cost(5), Terrain::Ground(5));
+    assert_eq!(Terrain::from_cost(WATER_COST), Terrain::Water);
+    assert_eq!(Terrain::from_cost(u32::MAX), Terrain::Wall);
+
+    let walker = CostProfile::default();
+    assert_eq!(walker, AgentClass::Walker.get_profile());
+    assert_eq!(walker.get_tile_cost(5), Some(5));
+    assert_eq!(walker.get_tile_cost(WATER_COST), None);
+
+    let boat = CostProfile::from(AgentClass::Boat);
+    assert_eq!(boat.get_tile_cost(5), None);
+    assert_eq!(boat.get_tile_cost(WATER_COST), Some(1));
+
+    let amphibious = CostProfile::from(AgentClass::Amphibious);
+    assert!(amphibious.can_enter(5) && amphibious.can_enter(WATER_COST));
+    assert!(!amphibious.can_enter(u32::MAX));
+    assert_eq!(amphibious.get_cost(Terrain::Ground(2)), Some(2));
+    assert_eq!(amphibious.get_cost(Terrain::Water), Some(3));
+}
+
+#[test]
+fn terrain_layers_round_trip_raw_costs() {
+    let map = get_map();
+    let layer = TerrainLayer::from(&map);
+
+    assert_eq!(layer.get((0, 0)), Terrain::Ground(1));
+    assert_eq!(layer.get((1, 0)), Terrain::Water);
+    assert_eq!(layer.to_costs(), map.data);
+    assert_eq!(GridMap::from(&layer).data, map.data);
+
+    for cost in [1, 7, WATER_COST, u32::MAX] {
+        assert_eq!(Terrain::from_cost(cost).to_cost(), cost);
+    }
+
+    // MovingAI maps go through the same tiles
+    let map = parse_map("type octile\nheight 1\nwidth 4\nmap\n.WT@\n").unwrap();
+    let layer = TerrainLayer::from(&map);
+
+    assert_eq!(layer.tiles, vec![Terrain::Ground(1), Terrain::Water, Terrain::Wall, Terrain::Wall]);
+    assert_eq!(layer.tiles.iter().map(|terrain| terrain.to_letter()).collect::<String>(), ".W@@");
+    assert!(!map.is_obstacle((1, 0)) && map.is_obstacle((2, 0)));
+}
+
+#[test]
+fn agents_follow_their_cost_profile() {
+    let map = get_map();
+
+    let tasks = vec![
+        AgentTask { profile: AgentClass::Walker.get_profile(), ..AgentTask::new(0, (0, 0), (6, 0)) },
+        AgentTask { profile: AgentClass::Boat.get_profile(), ..AgentTask::new(1, (1, 0), (5, 2)) },
+        AgentTask { profile: AgentClass::Amphibious.get_profile(), ..AgentTask::new(2, (6, 2), (3, 1)) }
+    ];
+
+    for mut solver in get_solvers() {
+        let solution = solver.solve(&map, &tasks).unwrap();
+
+        // Planned on their own, the A* agents may still run into each other
+        let violations = PlanValidator::new().validate(&WorldMap::from(&map), &tasks, &solution.paths);
+        let violations = violations.iter()
+            .filter(|violation| solver.name() != "astar" || !matches!(violation, Violation::VertexConflict { .. } | Violation::EdgeConflict { .. }))
+            .collect::<Vec<&Violation>>();
+        assert!(violations.is_empty(), "{} {:?}", solver.name(), violations);
+
+        // The walker went around the lake, the boat stayed on it
+        assert!(solution.paths[0].contains(&(3, 3)), "{}", solver.name());
+        assert!(solution.paths[1].iter().all(|pos| map.get_cost(*pos) == WATER_COST), "{}", solver.name());
+    }
+
+    // Only a swimmer reaches the island
+    let tasks = vec![AgentTask::new(0, (0, 3), (3, 1))];
+
+    for mut solver in get_solvers() {
+        let error = solver.solve(&map, &tasks).unwrap_err();
+        assert_eq!(error, PathfindingError::GoalUnreachable { start: (0, 3), goal: (3, 1) }, "{}", solver.name());
+    }
+}
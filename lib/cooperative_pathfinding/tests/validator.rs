@@ -0,0 +1,116 @@
+use cooperative_pathfinding::{ConflictBasedSearch, PlanValidator, PlannerConfig, Violation, WhcaSolver, WorldMap};
+use pathfinding_core::{AgentTask, DiagonalCrossing, GridMap, MapfSolver, MoveSet};
+
+const O: u32 = u32::MAX;
+
+/* Open room with a pillar in the middle */
+fn get_map(move_set: MoveSet) -> GridMap {
+    let mut data = vec![1; 8 * 6];
+    data[2 * 8 + 3] = O;
+    data[3 * 8 + 4] = O;
+
+    let mut map = GridMap::new(data, 8, 6);
+    map.move_set = move_set;
+    map
+}
+
+/* Agents crossing the room in both directions */
+fn get_tasks() -> Vec<AgentTask> {
+    vec![
+        AgentTask::new(0, (0, 2), (7, 2)),
+        AgentTask::new(1, (7, 3), (0, 3)),
+        AgentTask::new(2, (2, 0), (5, 5)),
+        AgentTask::new(3, (5, 0), (2, 5))
+    ]
+}
+
+#[test]
+fn solvers_are_conflict_free() {
+    let move_sets = [MoveSet::FourWay, MoveSet::EightWay, MoveSet::EightWayNoCornerCutting];
+
+    for move_set in move_sets.iter() {
+        let map = get_map(*move_set);
+        let tasks = get_tasks();
+        let solvers: Vec<Box<dyn MapfSolver>> = vec![Box::new(WhcaSolver::new()), Box::new(ConflictBasedSearch::new())];
+
+        for mut solver in solvers {
+            let solution = solver.solve(&map, &tasks).unwrap();
+            let violations = PlanValidator::new().validate(&WorldMap::from(&map), &tasks, &solution.paths);
+
+            assert!(violations.is_empty(), "{} with {:?}: {:?}", solver.name(), move_set, violations);
+        }
+    }
+}
+
+#[test]
+fn every_violation_is_reported() {
+    let map = WorldMap::from(&get_map(MoveSet::FourWay));
+    let tasks = vec![
+        AgentTask::new(0, (0, 0), (2, 0)),
+        AgentTask::new(1, (2, 0), (0, 0)),
+        AgentTask::new(2, (0, 5), (7, 5)),
+        AgentTask::new(3, (3, 1), (3, 3))
+    ];
+    let paths = vec![
+        vec![(0, 0), (1, 0), (2, 0)],
+        vec![(2, 0), (1, 0), (0, 0)],
+        vec![(1, 5), (3, 5)],
+        vec![(3, 1), (3, 2), (3, 3)]
+    ];
+
+    let violations = PlanValidator::new().validate(&map, &tasks, &paths);
+    let expected = [
+        Violation::WrongStart { agent: 2, pos: (1, 5), start: (0, 5) },
+        Violation::InvalidMove { agent: 2, from: (1, 5), to: (3, 5), time: 1 },
+        Violation::GoalNotReached { agent: 2, pos: (3, 5), goal: (7, 5) },
+        Violation::Obstacle { agent: 3, pos: (3, 2), time: 1 },
+        Violation::VertexConflict { agents: (0, 1), pos: (1, 0), time: 1 }
+    ];
+
+    for violation in expected.iter() {
+        assert!(violations.contains(violation), "missing {} in {:?}", violation, violations);
+    }
+    assert_eq!(violations.len(), expected.len(), "{:?}", violations);
+
+    // Swapping tiles is caught even when the agents never share one
+    let paths = vec![vec![(0, 0), (1, 0), (2, 0)], vec![(2, 0), (2, 0), (1, 0), (0, 0)]];
+    let violations = PlanValidator::new().validate(&map, &tasks[..2], &paths);
+    assert_eq!(violations, vec![Violation::EdgeConflict { agents: (0, 1), moves: (((1, 0), (2, 0)), ((2, 0), (1, 0))), time: 2 }]);
+}
+
+#[test]
+fn diagonal_crossings_follow_the_policy() {
+    // Two agents taking the diagonals of the same square
+    let map = GridMap::new(vec![1; 2 * 2], 2, 2);
+    let tasks = vec![
+        AgentTask::new(0, (0, 0), (1, 1)),
+        AgentTask::new(1, (1, 0), (0, 1))
+    ];
+    let forbidding = PlanValidator::new();
+    let allowing = PlanValidator { diagonal_crossing: DiagonalCrossing::Allow, ..PlanValidator::new() };
+
+    for diagonal_crossing in [DiagonalCrossing::Forbid, DiagonalCrossing::Allow].iter() {
+        let mut whca = WhcaSolver::new();
+        whca.config = PlannerConfig { diagonal_crossing: *diagonal_crossing, ..PlannerConfig::default() };
+
+        let mut cbs = ConflictBasedSearch::new();
+        cbs.diagonal_crossing = *diagonal_crossing;
+
+        let solvers: Vec<Box<dyn MapfSolver>> = vec![Box::new(whca), Box::new(cbs)];
+
+        for mut solver in solvers {
+            let solution = solver.solve(&map, &tasks).unwrap();
+            let crossings = forbidding.validate(&WorldMap::from(&map), &tasks, &solution.paths);
+
+            if *diagonal_crossing == DiagonalCrossing::Forbid {
+                assert!(crossings.is_empty(), "{}: {:?}", solver.name(), crossings);
+                assert!(solution.get_makespan() > 1, "{}: {:?}", solver.name(), solution.paths);
+            } else {
+                // Both agents cross at once, only a validator forbidding it complains
+                assert_eq!(solution.paths, vec![vec![(0, 0), (1, 1)], vec![(1, 0), (0, 1)]], "{}", solver.name());
+                assert!(allowing.validate(&WorldMap::from(&map), &tasks, &solution.paths).is_empty(), "{}", solver.name());
+                assert_eq!(crossings, vec![Violation::EdgeConflict { agents: (0, 1), moves: (((0, 0), (1, 1)), ((1, 0), (0, 1))), time: 1 }]);
+            }
+        }
+    }
+}
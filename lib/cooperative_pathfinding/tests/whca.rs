@@ -0,0 +1,398 @@
+use cooperative_pathfinding::{AgentStore, CoordinationEvent, GoalParking, Node, PlanValidator, PlannerConfig, PriorityStrategy, SpaceTimeMap, TieBreaking, Violation, WhcaSolver, WorldMap};
+use pathfinding_core::{AgentTask, CostProfile, Graph, GridMap, MapfSolver, Metrics, MoveSet};
+
+mod common;
+use common::get_corridor;
+
+#[path = "../../pathfinding_core/tests/common/mod.rs"]
+mod random;
+use random::Lcg;
+
+/* Open room with a wall splitting it, except for a four tiles gap */
+fn get_map() -> GridMap {
+    let mut data = vec![1; 10 * 8];
+    for y in 0..8 {
+        if !(2..6).contains(&y) {
+            data[y * 10 + 5] = u32::MAX;
+        }
+    }
+
+    GridMap::new(data, 10, 8)
+}
+
+fn get_tasks() -> Vec<AgentTask> {
+    vec![
+        AgentTask::new(0, (0, 1), (9, 6)),
+        AgentTask::new(1, (9, 0), (0, 6)),
+        AgentTask::new(2, (1, 7), (8, 1)),
+        AgentTask::new(3, (8, 7), (1, 0))
+    ]
+}
+
+#[test]
+fn windows_are_configurable() {
+    let map = get_map();
+    let tasks = get_tasks();
+    let configs = [
+        PlannerConfig::default(),
+        PlannerConfig::new(4, 2),
+        PlannerConfig::new(8, 8),
+        PlannerConfig { staggered_replanning: false, ..PlannerConfig::new(12, 4) },
+        PlannerConfig { tie_breaking: TieBreaking::HighestG, ..PlannerConfig::new(24, 6) },
+        PlannerConfig { tie_breaking: TieBreaking::LowestG, ..PlannerConfig::new(6, 1) },
+        PlannerConfig { priority: PriorityStrategy::Random { seed: 7 }, ..PlannerConfig::default() },
+        PlannerConfig { priority: PriorityStrategy::FarthestFirst, stall_windows: 1, ..PlannerConfig::new(4, 2) },
+        PlannerConfig { priority: PriorityStrategy::MostDelayedFirst, staggered_replanning: false, ..PlannerConfig::new(6, 3) },
+        PlannerConfig { goal_parking: GoalParking::Yield, ..PlannerConfig::new(8, 4) },
+        PlannerConfig { goal_parking: GoalParking::Yield, stall_windows: 1, ..PlannerConfig::new(4, 1) }
+    ];
+
+    for config in configs.iter() {
+        let mut solver = WhcaSolver::new();
+        solver.config = *config;
+
+        // Agents 1 and 3 plan further ahead, agent 2 asks for less than the replan interval
+        solver.window_sizes.insert(1, config.window_size * 2);
+        solver.window_sizes.insert(2, 1);
+        solver.window_sizes.insert(3, config.window_size + 3);
+
+        let solution = solver.solve(&map, &tasks).unwrap();
+        let violations = PlanValidator::new().validate(&WorldMap::from(&map), &tasks, &solution.paths);
+
+        assert!(violations.is_empty(), "{:?}: {:?}", config, violations);
+    }
+}
+
+#[test]
+fn replanning_is_staggered() {
+    let config = PlannerConfig::new(8, 4);
+    let offsets = (0..4).map(|n| config.get_replan_offset(n)).collect::<Vec<u32>>();
+
+    // Each agent of a replan interval replans on a step of its own, then the offsets repeat
+    assert_eq!(offsets, vec![0, 1, 2, 3]);
+    assert_eq!(config.get_replan_offset(6), 2);
+
+    let config = PlannerConfig { staggered_replanning: false, ..config };
+    assert!((0..4).all(|n| config.get_replan_offset(n) == 0));
+}
+
+#[test]
+fn agent_store_is_send_and_sync() {
+    fn is_send_and_sync<T: Send + Sync>() {}
+
+    is_send_and_sync::<AgentStore>();
+    is_send_and_sync::<AgentStore<usize>>();
+}
+
+#[test]
+fn heuristics_are_deterministic_across_threads() {
+    let map = get_map();
+    let tasks = get_tasks();
+
+    let paths = [1, 2, 8].iter()
+        .map(|threads| {
+            let mut solver = WhcaSolver::new();
+            solver.threads = *threads;
+            solver.solve(&map, &tasks).unwrap().paths
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(paths[0], paths[1]);
+    assert_eq!(paths[0], paths[2]);
+}
+
+#[test]
+fn vanished_agents_free_their_goal() {
+    // A corridor where the first agent stops on the way of the second one
+    let map = get_corridor(7, &[]);
+    let tasks = vec![
+        AgentTask::new(0, (1, 0), (2, 0)),
+        AgentTask::new(1, (0, 0), (6, 0))
+    ];
+
+    let mut solver = WhcaSolver::new();
+    solver.max_steps = 40;
+
+    // Resting on its goal, the first agent blocks the corridor for good
+    let solution = solver.solve(&map, &tasks).unwrap();
+    assert_eq!(solution.paths[0], vec![(1, 0), (2, 0)]);
+    assert_ne!(solution.paths[1].last(), Some(&(6, 0)));
+
+    solver.config.goal_parking = GoalParking::Vanish;
+    let solution = solver.solve(&map, &tasks).unwrap();
+
+    let validator = PlanValidator { agents_vanish: true, ..PlanValidator::new() };
+    let violations = validator.validate(&WorldMap::from(&map), &tasks, &solution.paths);
+    assert!(violations.is_empty(), "{:?}", violations);
+
+    let metrics = Metrics::new("whca", &map, &tasks, &solution);
+    assert_eq!(metrics.solved_agents, 2);
+    assert_eq!(metrics.vertex_conflicts, 0);
+}
+
+#[test]
+fn parked_agents_keep_their_goal() {
+    // A corridor with a pocket under the goal of the first agent
+    let map = get_corridor(9, &[4]);
+    let tasks = vec![
+        AgentTask::new(0, (3, 0), (4, 0)),
+        AgentTask::new(1, (0, 0), (8, 0))
+    ];
+
+    // The second agent replans at other steps than the first one, with a longer window
+    let mut solver = WhcaSolver::new();
+    solver.max_steps = 60;
+    solver.window_sizes.insert(1, 24);
+
+    // Staying on its goal, the first agent never steps into the pocket to let the second one through
+    let solution = solver.solve(&map, &tasks).unwrap();
+    assert_eq!(solution.paths[0], vec![(3, 0), (4, 0)]);
+
+    let violations = PlanValidator::new().validate(&WorldMap::from(&map), &tasks, &solution.paths);
+    assert_eq!(violations.len(), 1, "{:?}", violations);
+    assert!(matches!(violations[0], Violation::GoalNotReached { agent: 1, .. }), "{:?}", violations);
+
+    // The second agent waited until the last step, its path isn't cut short
+    assert_eq!(solution.paths[1].len(), 61);
+    assert_eq!(Metrics::new("whca", &map, &tasks, &solution).makespan, 60);
+}
+
+#[test]
+fn parked_agents_yield_their_goal() {
+    // A corridor with a way around it, the first agent rests on its goal in the middle of the corridor
+    // with a tile under it to step aside
+    let mut data = vec![1; 7 * 3];
+    for x in [1, 2, 4, 5].iter() {
+        data[7 + x] = u32::MAX;
+    }
+
+    let mut map = GridMap::new(data, 7, 3);
+    map.move_set = MoveSet::FourWay;
+
+    let tasks = vec![
+        AgentTask::new(0, (3, 0), (3, 0)),
+        AgentTask::new(1, (0, 0), (6, 0))
+    ];
+
+    let mut solver = WhcaSolver::new();
+    solver.max_steps = 40;
+
+    // Staying on its goal, the first agent has the second one go the long way around
+    let staying = solver.solve(&map, &tasks).unwrap();
+    assert_eq!(staying.paths[0], vec![(3, 0)]);
+
+    solver.config.goal_parking = GoalParking::Yield;
+
+    let yielding = solver.solve(&map, &tasks).unwrap();
+    let violations = PlanValidator::new().validate(&WorldMap::from(&map), &tasks, &yielding.paths);
+    assert!(violations.is_empty(), "{:?}", violations);
+
+    // Yielding, it steps aside and the second agent goes through the corridor
+    assert!(yielding.paths[0].contains(&(3, 1)));
+    assert!(yielding.paths[1].len() < staying.paths[1].len(), "{:?} {:?}", yielding.paths, staying.paths);
+
+    // The goal of the second agent lies behind the goal of the first one, only reached if the first one steps aside
+    let mut nook = GridMap::new(vec![1, u32::MAX, 1, 1, 1, 1, u32::MAX, 1, 1], 3, 3);
+    nook.move_set = MoveSet::FourWay;
+
+    let behind = vec![
+        AgentTask::new(0, (1, 1), (2, 1)),
+        AgentTask::new(1, (0, 1), (2, 0))
+    ];
+
+    let solution = solver.solve(&nook, &behind).unwrap();
+    let violations = PlanValidator::new().validate(&WorldMap::from(&nook), &behind, &solution.paths);
+    assert!(violations.is_empty(), "{:?}", violations);
+
+    // Staying, it never lets the second agent in
+    solver.config.goal_parking = GoalParking::Stay;
+    let solution = solver.solve(&nook, &behind).unwrap();
+    assert_eq!(Metrics::new("whca", &nook, &behind, &solution).solved_agents, 1);
+    solver.config.goal_parking = GoalParking::Yield;
+
+    // An agent only yields once it stands on its goal, not while it is still moving there
+    let mut data = vec![u32::MAX; 7 * 2];
+    data[1..4].iter_mut().for_each(|cost| *cost = 1);
+    data[7..13].iter_mut().for_each(|cost| *cost = 1);
+    data[7 + 2] = u32::MAX;
+
+    let mut dead_end = GridMap::new(data, 7, 2);
+    dead_end.move_set = MoveSet::FourWay;
+
+    let crossing = vec![
+        AgentTask::new(0, (5, 1), (2, 0)),
+        AgentTask::new(1, (0, 1), (3, 0))
+    ];
+
+    solver.config.window_size = 8;
+    solver.config.replan_interval = 4;
+
+    let solution = solver.solve(&dead_end, &crossing).unwrap();
+    let violations = PlanValidator::new().validate(&WorldMap::from(&dead_end), &crossing, &solution.paths);
+    assert!(violations.iter().all(|violation| matches!(violation, Violation::GoalNotReached { .. })), "{:?}", violations);
+
+    solver.config = PlannerConfig { goal_parking: GoalParking::Yield, ..PlannerConfig::default() };
+
+    // Without a pocket, it has nowhere to go and keeps its goal
+    let map = get_corridor(7, &[]);
+    let tasks = vec![
+        AgentTask::new(0, (1, 0), (3, 0)),
+        AgentTask::new(1, (0, 0), (6, 0))
+    ];
+
+    let solution = solver.solve(&map, &tasks).unwrap();
+    let violations = PlanValidator::new().validate(&WorldMap::from(&map), &tasks, &solution.paths);
+    assert_eq!(violations.len(), 1, "{:?}", violations);
+    assert!(violations.iter().all(|violation| matches!(violation, Violation::GoalNotReached { agent: 1, .. })));
+}
+
+#[test]
+fn yielding_agents_never_swap_with_the_others() {
+    // Crowded four-way rooms, where agents resting on their goal keep being displaced
+    for seed in 0..200 {
+        let mut random = Lcg::new(seed);
+
+        let data = (0..12 * 12)
+            .map(|_| if random.next_u32().is_multiple_of(5) { u32::MAX } else { 1 })
+            .collect::<Vec<u32>>();
+        let mut map = GridMap::new(data, 12, 12);
+        map.move_set = MoveSet::FourWay;
+
+        let mut used = Vec::new();
+        while used.len() < 16 {
+            let pos = (random.next_u32() % 12, random.next_u32() % 12);
+            if !map.is_obstacle(pos) && !used.contains(&pos) {
+                used.push(pos);
+            }
+        }
+
+        let tasks = (0..8)
+            .map(|id| AgentTask::new(id as u32, used[id], used[id + 8]))
+            .collect::<Vec<AgentTask>>();
+
+        let mut solver = WhcaSolver::new();
+        solver.config.goal_parking = GoalParking::Yield;
+        solver.max_steps = 100;
+
+        // Some goals are walled off from their start
+        let solution = match solver.solve(&map, &tasks) {
+            Ok(solution) => solution,
+            Err(_) => continue
+        };
+
+        let violations = PlanValidator::new().validate(&WorldMap::from(&map), &tasks, &solution.paths);
+        let conflicts = violations.iter()
+            .filter(|violation| matches!(violation, Violation::VertexConflict { .. } | Violation::EdgeConflict { .. }))
+            .collect::<Vec<&Violation>>();
+        assert!(conflicts.is_empty(), "seed {} {:?}", seed, conflicts);
+    }
+}
+
+#[test]
+fn deadlocks_are_resolved() {
+    // A corridor with a pocket, two agents facing each other have been waiting for the tile of the other
+    let map = WorldMap::from(&get_corridor(7, &[4]));
+
+    let solver = WhcaSolver::new();
+    let mut agents = AgentStore::new();
+    let mut space_time_map = SpaceTimeMap::new(solver.config.window_size);
+
+    for (start, goal) in [((2, 0), (6, 0)), ((3, 0), (0, 0))].iter() {
+        let id = agents.add("agent", Node::new(*start, 0), Node::new(*goal, 0), CostProfile::default(), &solver.config);
+        let agent = agents.get_mut(id).unwrap();
+
+        agent.portion_path = vec![Node::new(*start, 0); 4];
+        agent.waiting_steps = 2;
+        agent.replan_offset = 1;
+    }
+
+    let events = solver.step(&map, &mut space_time_map, &mut agents, 4).unwrap();
+    assert_eq!(events.len(), 1, "{:?}", events);
+
+    match &events[0] {
+        CoordinationEvent::Deadlock { step, agents, backing_off } => {
+            assert_eq!(*step, 4);
+            assert_eq!(agents.len(), 2);
+            assert_eq!(*backing_off, 2);
+        }
+        event => panic!("unexpected event {:?}", event)
+    }
+
+    // The second agent backed off into the pocket to let the first one through
+    let mut visited = Vec::new();
+
+    for steps in 5..30 {
+        solver.step(&map, &mut space_time_map, &mut agents, steps).unwrap();
+        visited.push(agents.get(2).unwrap().current_node.pos);
+    }
+
+    assert!(visited.contains(&(4, 1)));
+    assert!(agents.iter().all(|agent| agent.current_node == agent.goal));
+}
+
+#[test]
+fn livelocks_are_reported() {
+    // The second agent parks in the only way to the goal of the first one,
+    // which keeps walking back and forth in front of it
+    let mut data = vec![1; 5 * 2];
+    data[3] = u32::MAX;
+
+    let mut map = GridMap::new(data, 5, 2);
+    map.move_set = MoveSet::FourWay;
+
+    let tasks = vec![
+        AgentTask::new(0, (1, 1), (4, 0)),
+        AgentTask::new(1, (4, 1), (2, 1))
+    ];
+
+    let mut solver = WhcaSolver::new();
+    solver.max_steps = 60;
+    solver.solve(&map, &tasks).unwrap();
+
+    let livelocks = solver.events.iter()
+        .filter_map(|event| match event {
+            CoordinationEvent::Livelock { step, agent: 0, windows } => Some((*step, *windows)),
+            _ => None
+        })
+        .collect::<Vec<(u32, u32)>>();
+
+    // Once per window of the agent, whatever the replannings in between
+    assert!(livelocks.len() >= 2, "{:?}", solver.events);
+    assert_eq!(livelocks[0].1, solver.config.stall_windows);
+
+    for pair in livelocks.windows(2) {
+        assert_eq!(pair[1], (pair[0].0 + solver.config.replan_interval, pair[0].1 + 1), "{:?}", livelocks);
+    }
+
+    // Waiting behind a parked agent is a stall, not a livelock
+    let map = get_corridor(7, &[]);
+    let tasks = vec![
+        AgentTask::new(0, (1, 0), (2, 0)),
+        AgentTask::new(1, (0, 0), (6, 0))
+    ];
+
+    solver.solve(&map, &tasks).unwrap();
+    assert!(solver.events.iter().any(|event| matches!(event, CoordinationEvent::Stall { agent: 1, .. })), "{:?}", solver.events);
+    assert!(!solver.events.iter().any(|event| matches!(event, CoordinationEvent::Livelock { .. })), "{:?}", solver.events);
+}
+
+#[test]
+fn blocked_agents_are_planned_first() {
+    // Two agents meet head-on in a corridor, the pocket to step aside is behind the second one
+    let map = get_corridor(9, &[1]);
+
+    let tasks = vec![
+        AgentTask::new(0, (3, 0), (8, 0)),
+        AgentTask::new(1, (5, 0), (0, 0))
+    ];
+
+    let mut solver = WhcaSolver::new();
+    solver.max_steps = 60;
+
+    let solution = solver.solve(&map, &tasks).unwrap();
+    let violations = PlanValidator::new().validate(&WorldMap::from(&map), &tasks, &solution.paths);
+    assert!(violations.is_empty(), "{:?}", violations);
+
+    assert!(solver.events.iter().any(|event| matches!(event, CoordinationEvent::Blocked { .. })), "{:?}", solver.events);
+    assert_eq!(solution.stats.events.len(), solver.events.len());
+}
@@ -0,0 +1,44 @@
+use std::error::Error;
+use std::fmt;
+use std::fmt::Debug;
+
+/* Errors of the planners, N is the node type of the searched graph */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathfindingError<N = (u32, u32)> {
+    // The position lies outside of the map
+    OutOfBounds(N),
+
+    // The agent starts on an obstacle
+    StartBlocked(N),
+
+    // No path links the start to the goal
+    GoalUnreachable { start: N, goal: N },
+
+    // Two agents, given by task id, would have to rest on the same goal
+    DuplicateGoal { agents: (u32, u32), goal: N },
+
+    // Every move of the agent is reserved by the others over the whole window
+    WindowExhausted { agent: u32, pos: N },
+
+    // The search has been given up before finding a solution
+    ExpansionLimitReached(usize),
+
+    // Every agent can reach its goal, but not without colliding
+    NoSolution
+}
+
+impl<N: Debug> fmt::Display for PathfindingError<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PathfindingError::OutOfBounds(pos) => write!(f, "position {:?} is out of the map", pos),
+            PathfindingError::StartBlocked(pos) => write!(f, "start {:?} is an obstacle", pos),
+            PathfindingError::GoalUnreachable { start, goal } => write!(f, "goal {:?} is unreachable from {:?}", goal, start),
+            PathfindingError::DuplicateGoal { agents, goal } => write!(f, "agents {} and {} share the goal {:?}", agents.0, agents.1, goal),
+            PathfindingError::WindowExhausted { agent, pos } => write!(f, "agent {} has no free move from {:?} in its window", agent, pos),
+            PathfindingError::ExpansionLimitReached(limit) => write!(f, "no solution found within {} expansions", limit),
+            PathfindingError::NoSolution => write!(f, "no conflict-free solution exists")
+        }
+    }
+}
+
+impl<N: Debug> Error for PathfindingError<N> {}
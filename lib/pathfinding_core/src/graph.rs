@@ -0,0 +1,87 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+use crate::CostProfile;
+
+// Whether two agents may cross each other on the diagonals of the same
+// 2x2 square during the same tick, e.g. (0, 0) -> (1, 1) and (1, 0) -> (0, 1),
+// or more generally on the crossing edges given by the graph
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DiagonalCrossing {
+    Allow,
+    #[default]
+    Forbid
+}
+
+/* Map the planners search on, a grid or any navigation graph.
+Nodes are indexed from 0 to get_node_count, so planners can keep
+their search state in vectors */
+pub trait Graph {
+    type Node: Copy + Eq + Hash + Debug;
+
+    fn get_node_count(&self) -> usize;
+
+    fn get_index(&self, node: Self::Node) -> usize;
+
+    fn get_node(&self, index: usize) -> Self::Node;
+
+    fn get_nodes(&self) -> Vec<Self::Node> {
+        (0..self.get_node_count()).map(|index| self.get_node(index)).collect()
+    }
+
+    fn contains(&self, node: Self::Node) -> bool;
+
+    fn is_obstacle(&self, node: Self::Node) -> bool;
+
+    /* Nodes reachable in one move with the cost of that move, obstacles are left out */
+    fn get_neighbors(&self, node: Self::Node) -> Vec<(Self::Node, u32)>;
+
+    /* Nodes from which node is reachable in one move with the cost of that move,
+    used by the backward searches, the same as the neighbors on an undirected graph */
+    fn get_predecessors(&self, node: Self::Node) -> Vec<(Self::Node, u32)> {
+        self.get_neighbors(node)
+    }
+
+    /* Cost of staying on node for one timestep */
+    fn get_wait_cost(&self, node: Self::Node) -> u32;
+
+    /* The same queries for an agent of the given cost profile, the graphs without
+    any terrain are crossed the same way by every agent and ignore the profile */
+    fn is_obstacle_for(&self, node: Self::Node, _profile: &CostProfile) -> bool {
+        self.is_obstacle(node)
+    }
+
+    fn get_neighbors_for(&self, node: Self::Node, _profile: &CostProfile) -> Vec<(Self::Node, u32)> {
+        self.get_neighbors(node)
+    }
+
+    fn get_predecessors_for(&self, node: Self::Node, _profile: &CostProfile) -> Vec<(Self::Node, u32)> {
+        self.get_predecessors(node)
+    }
+
+    fn get_wait_cost_for(&self, node: Self::Node, _profile: &CostProfile) -> u32 {
+        self.get_wait_cost(node)
+    }
+
+    /* Estimation of the cost from one node to another, it must never overestimate it */
+    fn heuristic(&self, from: Self::Node, to: Self::Node) -> u32;
+
+    /* Moves that can't be made at the same time as from -> to by another agent,
+    like the other diagonal of the same cell on a grid */
+    fn get_crossing_edges(&self, _from: Self::Node, _to: Self::Node) -> Vec<(Self::Node, Self::Node)> {
+        Vec::new()
+    }
+
+    /* Whether two agents moving during the same timestep collide on the way, by swapping
+    their nodes or, when the policy forbids it, by taking crossing edges. A wait never does */
+    fn is_edge_conflict(&self, a_move: (Self::Node, Self::Node), b_move: (Self::Node, Self::Node), diagonal_crossing: DiagonalCrossing) -> bool {
+        if a_move.0 == a_move.1 || b_move.0 == b_move.1 {
+            return false;
+        }
+
+        let is_swap = a_move.0 == b_move.1 && a_move.1 == b_move.0;
+        let is_crossing = diagonal_crossing == DiagonalCrossing::Forbid && self.get_crossing_edges(a_move.0, a_move.1).contains(&b_move);
+
+        is_swap || is_crossing
+    }
+}
@@ -0,0 +1,84 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+// Costs are fixed point, a straight step on a tile of cost 1 costs STRAIGHT_COST
+// and a diagonal one √2 times more, rounded up so it is never underestimated
+pub const STRAIGHT_COST: u32 = 100;
+pub const DIAGONAL_COST: u32 = 142;
+
+/* Cost of entering a tile, staying in place costs as much as a straight step */
+pub fn get_move_cost(tile_cost: u32, diagonal: bool) -> u32 {
+    if diagonal {
+        tile_cost.saturating_mul(DIAGONAL_COST)
+    } else {
+        tile_cost.saturating_mul(STRAIGHT_COST)
+    }
+}
+
+/* Estimation of the cost between two positions, assuming every tile costs at least 1,
+a heuristic is admissible for a move set if it never overestimates the true cost */
+pub trait Heuristic: Debug + Send + Sync {
+    fn distance(&self, a: (u32, u32), b: (u32, u32)) -> u32;
+}
+
+fn get_deltas(a: (u32, u32), b: (u32, u32)) -> (u32, u32) {
+    (a.0.max(b.0) - a.0.min(b.0), a.1.max(b.1) - a.1.min(b.1))
+}
+
+// Admissible for 4-connected moves only
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Manhattan;
+
+impl Heuristic for Manhattan {
+    fn distance(&self, a: (u32, u32), b: (u32, u32)) -> u32 {
+        let (dx, dy) = get_deltas(a, b);
+        (dx + dy).saturating_mul(STRAIGHT_COST)
+    }
+}
+
+// Exact distance on an open 8-connected grid
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Octile;
+
+impl Heuristic for Octile {
+    fn distance(&self, a: (u32, u32), b: (u32, u32)) -> u32 {
+        let (dx, dy) = get_deltas(a, b);
+        let diagonal_steps = dx.min(dy);
+        let straight_steps = dx.max(dy) - diagonal_steps;
+        straight_steps.saturating_mul(STRAIGHT_COST).saturating_add(diagonal_steps.saturating_mul(DIAGONAL_COST))
+    }
+}
+
+// Counts diagonal steps as straight ones, admissible but less informed than Octile
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Chebyshev;
+
+impl Heuristic for Chebyshev {
+    fn distance(&self, a: (u32, u32), b: (u32, u32)) -> u32 {
+        let (dx, dy) = get_deltas(a, b);
+        dx.max(dy).saturating_mul(STRAIGHT_COST)
+    }
+}
+
+// Straight line distance, admissible for any move set
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Euclidean;
+
+impl Heuristic for Euclidean {
+    fn distance(&self, a: (u32, u32), b: (u32, u32)) -> u32 {
+        let (dx, dy) = get_deltas(a, b);
+        let length = ((dx as f64).powi(2) + (dy as f64).powi(2)).sqrt();
+        (length * STRAIGHT_COST as f64).floor().min(u32::MAX as f64) as u32
+    }
+}
+
+/* Pick a heuristic from its name */
+pub fn get_heuristic(name: &str) -> Option<Arc<dyn Heuristic>> {
+    match name {
+        "manhattan" => Some(Arc::new(Manhattan)),
+        "octile" => Some(Arc::new(Octile)),
+        "chebyshev" => Some(Arc::new(Chebyshev)),
+        "euclidean" => Some(Arc::new(Euclidean)),
+        _ => None
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::Duration;
+
+mod error;
+mod graph;
+mod heuristic;
+mod metrics;
+mod move_set;
+pub mod movingai;
+mod nav_graph;
+mod profile;
+mod terrain;
+
+pub use error::PathfindingError;
+pub use graph::{DiagonalCrossing, Graph};
+pub use metrics::Metrics;
+pub use nav_graph::NavGraph;
+pub use heuristic::{get_heuristic, get_move_cost, Chebyshev, Euclidean, Heuristic, Manhattan, Octile, DIAGONAL_COST, STRAIGHT_COST};
+pub use move_set::{Move, MoveSet};
+pub use profile::{AgentClass, CostProfile};
+pub use terrain::{Terrain, TerrainLayer, WATER_COST};
+
+// Position of an agent at each timestep, index 0 being its start,
+// once the path ends the agent stays on its last position
+pub type TimedPath<N = (u32, u32)> = Vec<N>;
+
+/* Raw grid shared by every planner, each tile holds its traversal cost,
+u32::MAX marks an obstacle and WATER_COST a water tile, see Terrain for the typed tiles */
+#[derive(Debug, Clone, Default)]
+pub struct GridMap {
+    pub data: Vec<u32>,
+    pub width: u32,
+    pub height: u32,
+    pub move_set: MoveSet
+}
+
+impl GridMap {
+    pub fn new(data: Vec<u32>, width: u32, height: u32) -> GridMap {
+        GridMap {
+            data,
+            width,
+            height,
+            ..Default::default()
+        }
+    }
+
+    pub fn get_cost(&self, pos: (u32, u32)) -> u32 {
+        self.data[self.get_index(pos)]
+    }
+
+    pub fn get_terrain(&self, pos: (u32, u32)) -> Terrain {
+        Terrain::from_cost(self.get_cost(pos))
+    }
+
+    /* Cost of a tile for an agent of the given profile, u32::MAX when it can't enter it */
+    pub fn get_tile_cost(&self, pos: (u32, u32), profile: &CostProfile) -> u32 {
+        profile.get_tile_cost(self.get_cost(pos)).unwrap_or(u32::MAX)
+    }
+
+    /* Cost of moving from pos to next for an agent of the given profile, staying in place
+    costs the tile once and diagonal moves get an extra cost for traversing */
+    pub fn get_move_cost(&self, pos: (u32, u32), next: (u32, u32), profile: &CostProfile) -> u32 {
+        get_move_cost(self.get_tile_cost(next, profile), pos.0 != next.0 && pos.1 != next.1)
+    }
+
+    /* Moves that an agent of the given profile can take from pos, waiting included, with the tile they lead to */
+    pub fn get_successors(&self, pos: (u32, u32), profile: &CostProfile) -> Vec<(Move, (u32, u32))> {
+        self.move_set.get_successors(pos, self.width, self.height, |pos| self.is_obstacle_for(pos, profile))
+    }
+
+    /* Will reject the tasks no planner can solve, before searching anything */
+    pub fn check_task(&self, task: &AgentTask) -> Result<(), PathfindingError> {
+
+        for pos in [task.start, task.goal].iter() {
+            if !self.contains(*pos) {
+                return Err(PathfindingError::OutOfBounds(*pos));
+            }
+        }
+
+        if self.is_obstacle_for(task.start, &task.profile) {
+            return Err(PathfindingError::StartBlocked(task.start));
+        }
+
+        if self.is_obstacle_for(task.goal, &task.profile) {
+            return Err(PathfindingError::GoalUnreachable { start: task.start, goal: task.goal });
+        }
+
+        Ok(())
+    }
+}
+
+/* The one grid implementation of the graph queries, the grid planners wrap a GridMap
+and answer through it. The moves without a profile are the ones of a walker */
+impl Graph for GridMap {
+    type Node = (u32, u32);
+
+    fn get_node_count(&self) -> usize {
+        self.data.len()
+    }
+
+    fn get_index(&self, pos: (u32, u32)) -> usize {
+        (pos.1 * self.width + pos.0) as usize
+    }
+
+    fn get_node(&self, index: usize) -> (u32, u32) {
+        (index as u32 % self.width, index as u32 / self.width)
+    }
+
+    fn contains(&self, pos: (u32, u32)) -> bool {
+        pos.0 < self.width && pos.1 < self.height
+    }
+
+    /* A wall, that no agent can enter whatever its profile */
+    fn is_obstacle(&self, pos: (u32, u32)) -> bool {
+        !self.get_terrain(pos).is_passable()
+    }
+
+    fn get_neighbors(&self, pos: (u32, u32)) -> Vec<((u32, u32), u32)> {
+        self.get_neighbors_for(pos, &CostProfile::default())
+    }
+
+    fn get_predecessors(&self, pos: (u32, u32)) -> Vec<((u32, u32), u32)> {
+        self.get_predecessors_for(pos, &CostProfile::default())
+    }
+
+    fn get_wait_cost(&self, pos: (u32, u32)) -> u32 {
+        self.get_wait_cost_for(pos, &CostProfile::default())
+    }
+
+    /* Whether an agent of the given profile can't enter the tile, like water for a walker */
+    fn is_obstacle_for(&self, pos: (u32, u32), profile: &CostProfile) -> bool {
+        !profile.can_enter(self.get_cost(pos))
+    }
+
+    /* Tiles reachable in one move from pos, following the move set */
+    fn get_neighbors_for(&self, pos: (u32, u32), profile: &CostProfile) -> Vec<((u32, u32), u32)> {
+        self.get_successors(pos, profile)
+            .into_iter()
+            .filter(|(step, _)| *step != Move::Wait)
+            .map(|(_, next)| (next, self.get_move_cost(pos, next, profile)))
+            .collect()
+    }
+
+    /* Reaching a tile costs the tile itself, whatever the tile we come from */
+    fn get_predecessors_for(&self, pos: (u32, u32), profile: &CostProfile) -> Vec<((u32, u32), u32)> {
+        self.get_neighbors_for(pos, profile)
+            .into_iter()
+            .map(|(previous, _)| (previous, self.get_move_cost(previous, pos, profile)))
+            .collect()
+    }
+
+    fn get_wait_cost_for(&self, pos: (u32, u32), profile: &CostProfile) -> u32 {
+        self.get_move_cost(pos, pos, profile)
+    }
+
+    fn heuristic(&self, from: (u32, u32), to: (u32, u32)) -> u32 {
+        Octile.distance(from, to)
+    }
+
+    /* A diagonal move crosses the other diagonal of the same 2x2 square */
+    fn get_crossing_edges(&self, from: (u32, u32), to: (u32, u32)) -> Vec<((u32, u32), (u32, u32))> {
+        if from.0 == to.0 || from.1 == to.1 {
+            return Vec::new();
+        }
+
+        let corner_a = (to.0, from.1);
+        let corner_b = (from.0, to.1);
+        vec![(corner_a, corner_b), (corner_b, corner_a)]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AgentTask<N = (u32, u32)> {
+    pub id: u32,
+    pub start: N,
+    pub goal: N,
+
+    // Terrain the agent can cross and at what cost, a walker by default
+    pub profile: CostProfile
+}
+
+impl<N> AgentTask<N> {
+    pub fn new(id: u32, start: N, goal: N) -> AgentTask<N> {
+        AgentTask {
+            id,
+            start,
+            goal,
+            profile: CostProfile::default()
+        }
+    }
+}
+
+impl<N: Copy + Eq + Hash> AgentTask<N> {
+
+    /* Will reject two agents resting on the same goal, they can't both stay there */
+    pub fn check_goals(tasks: &[AgentTask<N>]) -> Result<(), PathfindingError<N>> {
+        let mut goals = HashMap::new();
+
+        for task in tasks {
+            if let Some(id) = goals.insert(task.goal, task.id) {
+                return Err(PathfindingError::DuplicateGoal { agents: (id, task.id), goal: task.goal });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SolverStats {
+    pub planning_time: Duration,
+    pub nodes_expanded: usize,
+
+    // Same order as the tasks
+    pub nodes_expanded_per_agent: Vec<usize>,
+
+    // Planning time of each simulated tick, solvers planning everything at once have a single tick
+    pub tick_times: Vec<Duration>,
+
+    // What the solver noticed and resolved while planning, one line each
+    pub events: Vec<String>
+}
+
+/* Paths are given in the same order as the tasks */
+#[derive(Debug, Clone)]
+pub struct Solution<N = (u32, u32)> {
+    pub paths: Vec<TimedPath<N>>,
+    pub stats: SolverStats,
+
+    // Agents leave the map at the end of their path instead of staying on its last node
+    pub agents_vanish: bool,
+
+    // Whether the solver let agents cross each other on crossing edges
+    pub diagonal_crossing: DiagonalCrossing
+}
+
+impl<N> Default for Solution<N> {
+    fn default() -> Self {
+        Solution {
+            paths: Vec::new(),
+            stats: SolverStats::default(),
+            agents_vanish: false,
+            diagonal_crossing: DiagonalCrossing::default()
+        }
+    }
+}
+
+impl<N: Copy> Solution<N> {
+
+    /* Position of an agent at a given timestep, agents stay on their goal once arrived */
+    pub fn get_position(&self, agent: usize, time: usize) -> N {
+        let path = &self.paths[agent];
+        path[time.min(path.len() - 1)]
+    }
+
+    /* Whether the agent is still on the map at a given timestep */
+    pub fn is_present(&self, agent: usize, time: usize) -> bool {
+        !self.agents_vanish || time < self.paths[agent].len()
+    }
+
+    pub fn get_makespan(&self) -> usize {
+        self.paths.iter().map(|path| path.len().saturating_sub(1)).max().unwrap_or(0)
+    }
+}
+
+/* Common interface of the multi-agent planners,
+a solver takes a map and a set of tasks and returns one timed path per task */
+pub trait MapfSolver {
+    fn name(&self) -> &str;
+
+    fn solve(&mut self, map: &GridMap, tasks: &[AgentTask]) -> Result<Solution, PathfindingError>;
+}
@@ -0,0 +1,176 @@
+use std::time::Duration;
+
+use crate::{AgentTask, CostProfile, Graph, Solution};
+
+/* Quality and performance of one run, computed from its solution */
+#[derive(Debug, Clone, Default)]
+pub struct Metrics {
+    pub solver: String,
+    pub agents: usize,
+
+    // Agents whose path ends on their goal
+    pub solved_agents: usize,
+
+    // Sum over the agents of the timestep they arrive at, and the largest of them
+    pub sum_of_costs: usize,
+    pub makespan: usize,
+
+    // Sum over the agents of the cost of their moves and waits through their cost profile, in the
+    // fixed point costs of the graph. This is what CBS minimizes, a diagonal step or a costly tile
+    // weighs more than a timestep here while sum_of_costs counts every step the same
+    pub weighted_cost: u64,
+
+    // Timesteps an agent spent in place before its arrival
+    pub wait_actions: usize,
+
+    // Two agents on the same node at the same timestep, vanished agents excluded
+    pub vertex_conflicts: usize,
+
+    // Two agents swapping their nodes during the same timestep, or taking crossing edges
+    // when the solution forbids it, as PlanValidator reports them
+    pub edge_conflicts: usize,
+
+    pub nodes_expanded: usize,
+    pub nodes_expanded_per_agent: Vec<usize>,
+
+    pub planning_time: Duration,
+    pub tick_times: Vec<Duration>
+}
+
+impl Metrics {
+
+    pub fn new<G: Graph>(solver: &str, graph: &G, tasks: &[AgentTask<G::Node>], solution: &Solution<G::Node>) -> Metrics {
+
+        let mut metrics = Metrics {
+            solver: solver.into(),
+            agents: tasks.len(),
+            makespan: solution.get_makespan(),
+            nodes_expanded: solution.stats.nodes_expanded,
+            nodes_expanded_per_agent: solution.stats.nodes_expanded_per_agent.clone(),
+            planning_time: solution.stats.planning_time,
+            tick_times: solution.stats.tick_times.clone(),
+            ..Default::default()
+        };
+
+        for (task, path) in tasks.iter().zip(solution.paths.iter()) {
+            if path.last() == Some(&task.goal) {
+                metrics.solved_agents += 1;
+            }
+
+            metrics.sum_of_costs += path.len().saturating_sub(1);
+            metrics.weighted_cost += path.windows(2).map(|step| get_step_cost(graph, step[0], step[1], &task.profile) as u64).sum::<u64>();
+            metrics.wait_actions += path.windows(2).filter(|step| step[0] == step[1]).count();
+        }
+
+        for time in 0..=metrics.makespan {
+            for a in 0..solution.paths.len() {
+                for b in a + 1..solution.paths.len() {
+
+                    if !solution.is_present(a, time) || !solution.is_present(b, time) {
+                        continue;
+                    }
+
+                    let a_pos = solution.get_position(a, time);
+                    let b_pos = solution.get_position(b, time);
+
+                    if a_pos == b_pos {
+                        metrics.vertex_conflicts += 1;
+                    }
+
+                    if time > 0 {
+                        let a_move = (solution.get_position(a, time - 1), a_pos);
+                        let b_move = (solution.get_position(b, time - 1), b_pos);
+
+                        if graph.is_edge_conflict(a_move, b_move, solution.diagonal_crossing) {
+                            metrics.edge_conflicts += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        metrics
+    }
+
+    pub fn get_mean_tick_time(&self) -> Duration {
+        if self.tick_times.is_empty() {
+            return Duration::default();
+        }
+
+        self.tick_times.iter().sum::<Duration>() / self.tick_times.len() as u32
+    }
+
+    pub fn get_max_tick_time(&self) -> Duration {
+        self.tick_times.iter().max().copied().unwrap_or_default()
+    }
+
+    /* One JSON object, durations are given in microseconds */
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"solver\":\"{}\",\"agents\":{},\"solved_agents\":{},\"sum_of_costs\":{},\"weighted_cost\":{},\"makespan\":{},\
+\"wait_actions\":{},\"vertex_conflicts\":{},\"edge_conflicts\":{},\"nodes_expanded\":{},\
+\"nodes_expanded_per_agent\":[{}],\"planning_time_us\":{},\"tick_times_us\":[{}]}}",
+            self.solver.replace('\\', "\\\\").replace('"', "\\\""),
+            self.agents,
+            self.solved_agents,
+            self.sum_of_costs,
+            self.weighted_cost,
+            self.makespan,
+            self.wait_actions,
+            self.vertex_conflicts,
+            self.edge_conflicts,
+            self.nodes_expanded,
+            join(self.nodes_expanded_per_agent.iter(), ","),
+            self.planning_time.as_micros(),
+            join(self.tick_times.iter().map(Duration::as_micros), ",")
+        )
+    }
+
+    pub fn get_csv_header() -> &'static str {
+        "solver,agents,solved_agents,sum_of_costs,weighted_cost,makespan,wait_actions,vertex_conflicts,edge_conflicts,\
+nodes_expanded,nodes_expanded_per_agent,planning_time_us,ticks,mean_tick_time_us,max_tick_time_us"
+    }
+
+    /* One CSV row matching get_csv_header, the per agent expansions are separated by ';' */
+    pub fn to_csv(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+            self.solver.replace(',', ";"),
+            self.agents,
+            self.solved_agents,
+            self.sum_of_costs,
+            self.weighted_cost,
+            self.makespan,
+            self.wait_actions,
+            self.vertex_conflicts,
+            self.edge_conflicts,
+            self.nodes_expanded,
+            join(self.nodes_expanded_per_agent.iter(), ";"),
+            self.planning_time.as_micros(),
+            self.tick_times.len(),
+            self.get_mean_tick_time().as_micros(),
+            self.get_max_tick_time().as_micros()
+        )
+    }
+}
+
+/* Cost of one step of a path for an agent of the given profile, moves that the graph
+doesn't allow are left to the validator and cost nothing */
+fn get_step_cost<G: Graph>(graph: &G, from: G::Node, to: G::Node, profile: &CostProfile) -> u32 {
+    if !graph.contains(from) {
+        return 0;
+    }
+
+    if from == to {
+        return graph.get_wait_cost_for(from, profile);
+    }
+
+    graph.get_neighbors_for(from, profile)
+        .into_iter()
+        .find(|(next, _)| *next == to)
+        .map_or(0, |(_, cost)| cost)
+}
+
+fn join<T: ToString, I: Iterator<Item = T>>(values: I, separator: &str) -> String {
+    values.map(|value| value.to_string()).collect::<Vec<String>>().join(separator)
+}
@@ -0,0 +1,103 @@
+type Position = (u32, u32);
+
+const STRAIGHT_STEPS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const DIAGONAL_STEPS: [(i32, i32); 4] = [(1, 1), (-1, -1), (-1, 1), (1, -1)];
+
+/* Actions an agent can take during one timestep */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Move {
+    // Stay on the current tile
+    Wait,
+
+    // Move by the given offset
+    Step(i32, i32)
+}
+
+impl Move {
+    pub fn is_diagonal(&self) -> bool {
+        match self {
+            Move::Wait => false,
+            Move::Step(dx, dy) => *dx != 0 && *dy != 0
+        }
+    }
+
+    /* Tile reached by this move, None if it leaves a width x height map */
+    pub fn apply(&self, pos: Position, width: u32, height: u32) -> Option<Position> {
+        match self {
+            Move::Wait => Some(pos),
+            Move::Step(dx, dy) => {
+                let x = pos.0 as i64 + *dx as i64;
+                let y = pos.1 as i64 + *dy as i64;
+
+                if x < 0 || y < 0 || x >= width as i64 || y >= height as i64 {
+                    None
+                } else {
+                    Some((x as u32, y as u32))
+                }
+            }
+        }
+    }
+}
+
+/* Moves allowed on a grid, waiting is always allowed on top of them */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MoveSet {
+    // Orthogonal moves only
+    FourWay,
+
+    // Orthogonal and diagonal moves, even between two obstacles touching at a corner
+    #[default]
+    EightWay,
+
+    // Diagonal moves are only allowed when both orthogonal tiles they pass by are free
+    EightWayNoCornerCutting
+}
+
+impl MoveSet {
+
+    /* Every move of the set, the wait first */
+    pub fn get_moves(&self) -> Vec<Move> {
+        let mut moves = vec![Move::Wait];
+        moves.extend(STRAIGHT_STEPS.iter().map(|(dx, dy)| Move::Step(*dx, *dy)));
+
+        if *self != MoveSet::FourWay {
+            moves.extend(DIAGONAL_STEPS.iter().map(|(dx, dy)| Move::Step(*dx, *dy)));
+        }
+
+        moves
+    }
+
+    /* Moves that can be taken from pos with the tile they lead to,
+    moves leaving the map or entering an obstacle are left out */
+    pub fn get_successors<F>(&self, pos: Position, width: u32, height: u32, is_obstacle: F) -> Vec<(Move, Position)>
+    where F: Fn(Position) -> bool {
+
+        self.get_moves()
+            .into_iter()
+            .filter_map(|step| step.apply(pos, width, height).map(|next| (step, next)))
+            .filter(|(step, next)| {
+                if is_obstacle(*next) {
+                    return false;
+                }
+
+                // Both tiles next to the diagonal have to be free, so the agent doesn't cut the corner
+                if *self == MoveSet::EightWayNoCornerCutting && step.is_diagonal() {
+                    return !is_obstacle((next.0, pos.1)) && !is_obstacle((pos.0, next.1));
+                }
+
+                true
+            })
+            .collect()
+    }
+
+    /* Tiles reachable from pos in one move, without waiting */
+    pub fn get_neighbors<F>(&self, pos: Position, width: u32, height: u32, is_obstacle: F) -> Vec<Position>
+    where F: Fn(Position) -> bool {
+
+        self.get_successors(pos, width, height, is_obstacle)
+            .into_iter()
+            .filter(|(step, _)| *step != Move::Wait)
+            .map(|(_, next)| next)
+            .collect()
+    }
+}
@@ -0,0 +1,158 @@
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::{AgentTask, GridMap, Terrain};
+
+#[derive(Debug)]
+pub enum LoadError {
+    // The file can't be read
+    Io(io::Error),
+
+    // The content doesn't follow the MovingAI format, lines are counted from 1
+    Parse { line: usize, message: String }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadError::Io(error) => write!(f, "{}", error),
+            LoadError::Parse { line, message } => write!(f, "line {}: {}", line, message)
+        }
+    }
+}
+
+impl Error for LoadError {}
+
+impl From<io::Error> for LoadError {
+    fn from(error: io::Error) -> Self {
+        LoadError::Io(error)
+    }
+}
+
+fn parse_error(line: usize, message: &str) -> LoadError {
+    LoadError::Parse { line, message: message.into() }
+}
+
+/* Will read a MovingAI .map file */
+pub fn load_map<P: AsRef<Path>>(path: P) -> Result<GridMap, LoadError> {
+    parse_map(&fs::read_to_string(path)?)
+}
+
+/* Will parse the content of a MovingAI .map file, made of a header
+(type, height, width, map) followed by one line of terrain letters per row */
+pub fn parse_map(content: &str) -> Result<GridMap, LoadError> {
+
+    let mut lines = content.lines().enumerate();
+    let mut width = None;
+    let mut height = None;
+    let mut header_lines = 0;
+
+    for (i, line) in lines.by_ref() {
+        header_lines = i + 1;
+        let mut words = line.split_whitespace();
+
+        match (words.next(), words.next()) {
+            (Some("type"), _) => {}
+            (Some("height"), Some(value)) => {
+                height = Some(value.parse::<u32>().map_err(|_| parse_error(i + 1, "invalid height"))?);
+            }
+            (Some("width"), Some(value)) => {
+                width = Some(value.parse::<u32>().map_err(|_| parse_error(i + 1, "invalid width"))?);
+            }
+            (Some("map"), None) => break,
+            (None, _) => {}
+            _ => return Err(parse_error(i + 1, "unknown header line"))
+        }
+    }
+
+    let (width, height) = match (width, height) {
+        (Some(width), Some(height)) => (width, height),
+        _ => return Err(parse_error(1, "missing width or height in the header"))
+    };
+
+    let tiles = width.checked_mul(height)
+        .map(|tiles| tiles as usize)
+        .ok_or_else(|| parse_error(header_lines, &format!("a {}x{} map is too large", width, height)))?;
+
+    // Each tile takes at least one byte of the content, a wrong header can't reserve more
+    let mut data = Vec::<u32>::with_capacity(tiles.min(content.len()));
+
+    for (i, line) in lines.take(height as usize) {
+        let row = line.trim_end();
+
+        if row.chars().count() != width as usize {
+            return Err(parse_error(i + 1, &format!("expected {} tiles, found {}", width, row.chars().count())));
+        }
+
+        for terrain in row.chars() {
+            let terrain = Terrain::from_letter(terrain)
+                .ok_or_else(|| parse_error(i + 1, &format!("unknown terrain '{}'", terrain)))?;
+            data.push(terrain.to_cost());
+        }
+    }
+
+    if data.len() != tiles {
+        return Err(parse_error(content.lines().count(), &format!("expected {} rows", height)));
+    }
+
+    Ok(GridMap::new(data, width, height))
+}
+
+/* Will read a MovingAI .scen file */
+pub fn load_scenario<P: AsRef<Path>>(path: P) -> Result<Vec<AgentTask>, LoadError> {
+    parse_scenario(&fs::read_to_string(path)?)
+}
+
+/* Will parse the content of a MovingAI .scen file, after the version line each
+line holds: bucket, map, width, height, start x, start y, goal x, goal y, optimal length.
+Tasks are numbered from 0 in the order of the file, the bucket and optimal length are ignored */
+pub fn parse_scenario(content: &str) -> Result<Vec<AgentTask>, LoadError> {
+
+    let mut tasks = Vec::<AgentTask>::new();
+
+    for (i, line) in content.lines().enumerate() {
+
+        if line.trim().is_empty() || line.starts_with("version") {
+            continue;
+        }
+
+        // The map name may hold spaces, but the fields are tab separated
+        let fields = if line.contains('\t') {
+            line.split('\t').map(str::trim).collect::<Vec<&str>>()
+        } else {
+            line.split_whitespace().collect::<Vec<&str>>()
+        };
+
+        if fields.len() < 8 {
+            return Err(parse_error(i + 1, "expected at least 8 fields"));
+        }
+
+        let mut size = [0; 2];
+
+        for (length, field) in size.iter_mut().zip(fields[2..4].iter()) {
+            *length = field.parse::<u32>().map_err(|_| parse_error(i + 1, &format!("invalid map size '{}'", field)))?;
+        }
+
+        let mut coordinates = [0; 4];
+
+        for (coordinate, field) in coordinates.iter_mut().zip(fields[4..8].iter()) {
+            *coordinate = field.parse::<u32>().map_err(|_| parse_error(i + 1, &format!("invalid coordinate '{}'", field)))?;
+        }
+
+        // Coordinates are checked against the size of the map given on the line
+        if coordinates[0] >= size[0] || coordinates[2] >= size[0] || coordinates[1] >= size[1] || coordinates[3] >= size[1] {
+            return Err(parse_error(i + 1, &format!("coordinates out of the {}x{} map", size[0], size[1])));
+        }
+
+        tasks.push(AgentTask::new(
+            tasks.len() as u32,
+            (coordinates[0], coordinates[1]),
+            (coordinates[2], coordinates[3])
+        ));
+    }
+
+    Ok(tasks)
+}
@@ -0,0 +1,101 @@
+use std::sync::Arc;
+
+use crate::{Euclidean, Graph, Heuristic, STRAIGHT_COST};
+
+/* Waypoints linked by weighted edges, like road networks or the navigation graphs
+exported from the level editor. Each waypoint keeps its position for the heuristic,
+so edge costs are expected in the same unit, STRAIGHT_COST per unit of distance */
+#[derive(Debug, Clone)]
+pub struct NavGraph {
+    positions: Vec<(u32, u32)>,
+    edges: Vec<Vec<(usize, u32)>>,
+    reverse_edges: Vec<Vec<(usize, u32)>>,
+
+    // Cost of staying on a waypoint for one timestep
+    pub wait_cost: u32,
+
+    pub heuristic: Arc<dyn Heuristic>
+}
+
+impl Default for NavGraph {
+    fn default() -> Self {
+        NavGraph::new()
+    }
+}
+
+impl NavGraph {
+
+    pub fn new() -> NavGraph {
+        NavGraph {
+            positions: Vec::new(),
+            edges: Vec::new(),
+            reverse_edges: Vec::new(),
+            wait_cost: STRAIGHT_COST,
+            heuristic: Arc::new(Euclidean)
+        }
+    }
+
+    /* Add a waypoint and return its node */
+    pub fn add_node(&mut self, pos: (u32, u32)) -> usize {
+        self.positions.push(pos);
+        self.edges.push(Vec::new());
+        self.reverse_edges.push(Vec::new());
+        self.positions.len() - 1
+    }
+
+    /* One way edge, like a one way street */
+    pub fn add_edge(&mut self, from: usize, to: usize, cost: u32) {
+        self.edges[from].push((to, cost));
+        self.reverse_edges[to].push((from, cost));
+    }
+
+    /* Edge traversable both ways for the same cost */
+    pub fn connect(&mut self, a: usize, b: usize, cost: u32) {
+        self.add_edge(a, b, cost);
+        self.add_edge(b, a, cost);
+    }
+
+    pub fn get_position(&self, node: usize) -> (u32, u32) {
+        self.positions[node]
+    }
+}
+
+impl Graph for NavGraph {
+    type Node = usize;
+
+    fn get_node_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    fn get_index(&self, node: usize) -> usize {
+        node
+    }
+
+    fn get_node(&self, index: usize) -> usize {
+        index
+    }
+
+    fn contains(&self, node: usize) -> bool {
+        node < self.positions.len()
+    }
+
+    fn is_obstacle(&self, _node: usize) -> bool {
+        false
+    }
+
+    fn get_neighbors(&self, node: usize) -> Vec<(usize, u32)> {
+        self.edges[node].clone()
+    }
+
+    fn get_predecessors(&self, node: usize) -> Vec<(usize, u32)> {
+        self.reverse_edges[node].clone()
+    }
+
+    fn get_wait_cost(&self, _node: usize) -> u32 {
+        self.wait_cost
+    }
+
+    fn heuristic(&self, from: usize, to: usize) -> u32 {
+        self.heuristic.distance(self.positions[from], self.positions[to])
+    }
+}
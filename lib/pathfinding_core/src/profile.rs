@@ -0,0 +1,76 @@
+use crate::Terrain;
+
+/* Cost table of an agent for each terrain, None where it can't go.
+The base cost of a tile is multiplied by the cost of its terrain, a water tile having a base cost of 1.
+Costs of at least 1 keep the heuristics admissible */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CostProfile {
+    pub ground: Option<u32>,
+    pub water: Option<u32>
+}
+
+impl Default for CostProfile {
+    fn default() -> Self {
+        AgentClass::default().get_profile()
+    }
+}
+
+impl CostProfile {
+
+    pub fn new(ground: Option<u32>, water: Option<u32>) -> CostProfile {
+        CostProfile {
+            ground,
+            water
+        }
+    }
+
+    /* Cost of entering a tile of the given terrain for this agent, None when it can't enter it */
+    pub fn get_cost(&self, terrain: Terrain) -> Option<u32> {
+        let factor = match terrain {
+            Terrain::Ground(_) => self.ground,
+            Terrain::Water => self.water,
+            Terrain::Wall => None
+        };
+
+        Some(terrain.get_base_cost()?.saturating_mul(factor?))
+    }
+
+    /* Same from the raw cost of the tile */
+    pub fn get_tile_cost(&self, tile_cost: u32) -> Option<u32> {
+        self.get_cost(Terrain::from_cost(tile_cost))
+    }
+
+    pub fn can_enter(&self, tile_cost: u32) -> bool {
+        self.get_tile_cost(tile_cost).is_some()
+    }
+}
+
+/* Kinds of agents, each with its own cost table */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AgentClass {
+    // Stays on ground
+    #[default]
+    Walker,
+
+    // Stays on water
+    Boat,
+
+    // Goes anywhere, swimming is slower than walking
+    Amphibious
+}
+
+impl AgentClass {
+    pub fn get_profile(&self) -> CostProfile {
+        match self {
+            AgentClass::Walker => CostProfile::new(Some(1), None),
+            AgentClass::Boat => CostProfile::new(None, Some(1)),
+            AgentClass::Amphibious => CostProfile::new(Some(1), Some(3))
+        }
+    }
+}
+
+impl From<AgentClass> for CostProfile {
+    fn from(class: AgentClass) -> Self {
+        class.get_profile()
+    }
+}
@@ -0,0 +1,133 @@
+use crate::GridMap;
+
+// Raw cost of the water tiles of a grid, u32::MAX marking the obstacles
+pub const WATER_COST: u32 = u32::MAX - 1;
+
+/* Kind of a tile, the typed view of the raw costs of a grid */
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Terrain {
+    // Walkable tile costing the given amount
+    Ground(u32),
+
+    // Only crossed by the agents whose cost profile allows it
+    Water,
+
+    // Never crossed
+    Wall
+}
+
+impl Default for Terrain {
+    fn default() -> Self {
+        Terrain::Ground(1)
+    }
+}
+
+impl Terrain {
+
+    /* Will read a tile from its raw cost */
+    pub fn from_cost(tile_cost: u32) -> Terrain {
+        match tile_cost {
+            u32::MAX => Terrain::Wall,
+            WATER_COST => Terrain::Water,
+            cost => Terrain::Ground(cost)
+        }
+    }
+
+    /* Raw cost of the tile, as stored in a GridMap */
+    pub fn to_cost(&self) -> u32 {
+        match self {
+            Terrain::Ground(cost) => *cost,
+            Terrain::Water => WATER_COST,
+            Terrain::Wall => u32::MAX
+        }
+    }
+
+    /* Cost of crossing the tile before the cost profile of the agent applies, None for a wall */
+    pub fn get_base_cost(&self) -> Option<u32> {
+        match self {
+            Terrain::Ground(cost) => Some(*cost),
+            Terrain::Water => Some(1),
+            Terrain::Wall => None
+        }
+    }
+
+    /* Whether some agent can cross the tile, whether a given agent can is up to its cost profile */
+    pub fn is_passable(&self) -> bool {
+        *self != Terrain::Wall
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Terrain::Ground(_) => "ground",
+            Terrain::Water => "water",
+            Terrain::Wall => "wall"
+        }
+    }
+
+    /* Will read a MovingAI terrain letter, ground (. G) and swamp (S) are plain ground,
+    out of bounds (@ O) and trees (T) are walls */
+    pub fn from_letter(letter: char) -> Option<Terrain> {
+        match letter {
+            '.' | 'G' | 'S' => Some(Terrain::Ground(1)),
+            'W' => Some(Terrain::Water),
+            '@' | 'O' | 'T' => Some(Terrain::Wall),
+            _ => None
+        }
+    }
+
+    /* MovingAI letter of the tile, ground of any cost is written as '.' */
+    pub fn to_letter(&self) -> char {
+        match self {
+            Terrain::Ground(_) => '.',
+            Terrain::Water => 'W',
+            Terrain::Wall => '@'
+        }
+    }
+}
+
+/* Typed tiles of a grid, row by row, converting to and from the raw costs of a GridMap
+so that drawing a map and planning on it read the same tiles */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TerrainLayer {
+    pub tiles: Vec<Terrain>,
+    pub width: u32,
+    pub height: u32
+}
+
+impl TerrainLayer {
+    pub fn new(tiles: Vec<Terrain>, width: u32, height: u32) -> TerrainLayer {
+        TerrainLayer {
+            tiles,
+            width,
+            height
+        }
+    }
+
+    pub fn from_costs(data: &[u32], width: u32, height: u32) -> TerrainLayer {
+        TerrainLayer::new(data.iter().map(|cost| Terrain::from_cost(*cost)).collect(), width, height)
+    }
+
+    pub fn to_costs(&self) -> Vec<u32> {
+        self.tiles.iter().map(|terrain| terrain.to_cost()).collect()
+    }
+
+    pub fn get(&self, pos: (u32, u32)) -> Terrain {
+        self.tiles[(pos.1 * self.width + pos.0) as usize]
+    }
+
+    pub fn set(&mut self, pos: (u32, u32), terrain: Terrain) {
+        self.tiles[(pos.1 * self.width + pos.0) as usize] = terrain;
+    }
+}
+
+impl From<&GridMap> for TerrainLayer {
+    fn from(map: &GridMap) -> Self {
+        TerrainLayer::from_costs(&map.data, map.width, map.height)
+    }
+}
+
+impl From<&TerrainLayer> for GridMap {
+    fn from(layer: &TerrainLayer) -> Self {
+        GridMap::new(layer.to_costs(), layer.width, layer.height)
+    }
+}
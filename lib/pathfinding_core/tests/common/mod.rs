@@ -0,0 +1,19 @@
+/* Linear congruential generator, the same seed always gives the same numbers,
+so the test maps and the benchmark scenarios are the same on every run */
+pub struct Lcg {
+    state: u64
+}
+
+impl Lcg {
+    pub fn new(seed: u64) -> Lcg {
+        Lcg {
+            state: seed
+        }
+    }
+
+    /* Next number of the sequence, made of the high bits of the state */
+    pub fn next_u32(&mut self) -> u32 {
+        self.state = self.state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.state >> 33) as u32
+    }
+}
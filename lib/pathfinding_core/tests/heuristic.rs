@@ -0,0 +1,129 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use pathfinding_core::{get_move_cost, Chebyshev, Euclidean, Graph, GridMap, Heuristic, Manhattan, MoveSet, Octile};
+
+mod common;
+use common::Lcg;
+
+/* The same seed always gives the same map */
+fn get_random_map(seed: u64, width: u32, height: u32, max_cost: u32) -> GridMap {
+    let mut random = Lcg::new(seed);
+    let data = (0..width * height)
+        .map(|_| {
+            let roll = random.next_u32();
+
+            if roll.is_multiple_of(5) {
+                u32::MAX
+            } else {
+                1 + roll % max_cost
+            }
+        })
+        .collect();
+
+    GridMap::new(data, width, height)
+}
+
+/* True cost from every position to the goal, with a backward Dijkstra */
+fn get_true_distances(map: &GridMap, goal: (u32, u32)) -> Vec<u32> {
+    let index = |pos: (u32, u32)| (pos.1 * map.width + pos.0) as usize;
+    let mut distances = vec![u32::MAX; map.data.len()];
+    let mut open_set = BinaryHeap::new();
+
+    distances[index(goal)] = 0;
+    open_set.push(Reverse((0, goal)));
+
+    while let Some(Reverse((cost, pos))) = open_set.pop() {
+        if cost > distances[index(pos)] {
+            continue;
+        }
+
+        // Every move set is symmetric, the tiles reaching pos are its neighbors
+        for previous in map.move_set.get_neighbors(pos, map.width, map.height, |pos| map.is_obstacle(pos)) {
+
+            // Moving from previous to pos costs the tile of pos
+            let diagonal = previous.0 != pos.0 && previous.1 != pos.1;
+            let new_cost = cost.saturating_add(get_move_cost(map.data[index(pos)], diagonal));
+
+            if new_cost < distances[index(previous)] {
+                distances[index(previous)] = new_cost;
+                open_set.push(Reverse((new_cost, previous)));
+            }
+        }
+    }
+
+    distances
+}
+
+/* Will return the first position where the heuristic overestimates the true cost */
+fn find_overestimation(heuristic: &dyn Heuristic, move_set: MoveSet, max_cost: u32) -> Option<((u32, u32), (u32, u32))> {
+    for seed in 0..8 {
+        let mut map = get_random_map(seed, 24, 24, max_cost);
+        map.move_set = move_set;
+
+        for goal_index in (0..map.data.len()).step_by(37) {
+            let goal = (goal_index as u32 % map.width, goal_index as u32 / map.width);
+
+            if map.is_obstacle(goal) {
+                continue;
+            }
+
+            let distances = get_true_distances(&map, goal);
+
+            for (i, distance) in distances.iter().enumerate() {
+                let pos = (i as u32 % map.width, i as u32 / map.width);
+
+                if *distance != u32::MAX && heuristic.distance(pos, goal) > *distance {
+                    return Some((pos, goal));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+#[test]
+fn every_heuristic_is_admissible_with_four_way_moves() {
+    let heuristics: [&dyn Heuristic; 4] = [&Manhattan, &Octile, &Chebyshev, &Euclidean];
+
+    for heuristic in heuristics.iter() {
+        assert_eq!(find_overestimation(*heuristic, MoveSet::FourWay, 1), None, "{:?} on unit costs", heuristic);
+        assert_eq!(find_overestimation(*heuristic, MoveSet::FourWay, 9), None, "{:?} on weighted costs", heuristic);
+    }
+}
+
+#[test]
+fn diagonal_heuristics_are_admissible_with_eight_way_moves() {
+    let heuristics: [&dyn Heuristic; 3] = [&Octile, &Chebyshev, &Euclidean];
+
+    for move_set in [MoveSet::EightWay, MoveSet::EightWayNoCornerCutting].iter() {
+        for heuristic in heuristics.iter() {
+            assert_eq!(find_overestimation(*heuristic, *move_set, 1), None, "{:?} on unit costs with {:?}", heuristic, move_set);
+            assert_eq!(find_overestimation(*heuristic, *move_set, 9), None, "{:?} on weighted costs with {:?}", heuristic, move_set);
+        }
+    }
+}
+
+#[test]
+fn manhattan_overestimates_with_eight_way_moves() {
+    assert!(find_overestimation(&Manhattan, MoveSet::EightWay, 1).is_some());
+}
+
+#[test]
+fn exact_heuristics_on_an_open_map() {
+    let mut map = GridMap::new(vec![1; 16 * 16], 16, 16);
+    let goal = (5, 11);
+
+    map.move_set = MoveSet::FourWay;
+    let four_way_distances = get_true_distances(&map, goal);
+
+    map.move_set = MoveSet::EightWay;
+    let eight_way_distances = get_true_distances(&map, goal);
+
+    for i in 0..map.data.len() {
+        let pos = (i as u32 % map.width, i as u32 / map.width);
+
+        assert_eq!(Manhattan.distance(pos, goal), four_way_distances[i]);
+        assert_eq!(Octile.distance(pos, goal), eight_way_distances[i]);
+    }
+}
@@ -0,0 +1,112 @@
+use std::time::Duration;
+use pathfinding_core::{AgentTask, DiagonalCrossing, GridMap, Metrics, Solution};
+
+/* Open room with a costly tile */
+fn get_map() -> GridMap {
+    let mut map = GridMap::new(vec![1; 5 * 3], 5, 3);
+    map.data[2 * 5 + 2] = 3;
+    map
+}
+
+/* Three agents, the first waits once for the second to step diagonally,
+the third waits twice and never reaches its goal */
+fn get_solution() -> (Vec<AgentTask>, Solution) {
+    let tasks = vec![
+        AgentTask::new(0, (0, 0), (2, 0)),
+        AgentTask::new(1, (3, 0), (4, 1)),
+        AgentTask::new(2, (0, 2), (4, 2))
+    ];
+
+    let mut solution = Solution {
+        paths: vec![
+            vec![(0, 0), (1, 0), (1, 0), (2, 0)],
+            vec![(3, 0), (4, 1)],
+            vec![(0, 2), (1, 2), (1, 2), (1, 2), (2, 2)]
+        ],
+        ..Default::default()
+    };
+    solution.stats.nodes_expanded = 12;
+    solution.stats.nodes_expanded_per_agent = vec![5, 2, 5];
+    solution.stats.planning_time = Duration::from_micros(1500);
+    solution.stats.tick_times = vec![Duration::from_micros(100), Duration::from_micros(300)];
+
+    (tasks, solution)
+}
+
+#[test]
+fn metrics_are_computed_from_the_paths() {
+    let (tasks, solution) = get_solution();
+    let metrics = Metrics::new("test", &get_map(), &tasks, &solution);
+
+    assert_eq!(metrics.agents, 3);
+    assert_eq!(metrics.solved_agents, 2);
+    assert_eq!(metrics.sum_of_costs, 3 + 1 + 4);
+
+    // Waits cost their tile like moves, the diagonal and the costly tile weigh more than a step
+    assert_eq!(metrics.weighted_cost, 3 * 100 + 142 + (3 * 100 + 300));
+    assert_eq!(metrics.makespan, 4);
+    assert_eq!(metrics.wait_actions, 1 + 2);
+    assert_eq!(metrics.vertex_conflicts, 0);
+    assert_eq!(metrics.edge_conflicts, 0);
+    assert_eq!(metrics.get_mean_tick_time(), Duration::from_micros(200));
+    assert_eq!(metrics.get_max_tick_time(), Duration::from_micros(300));
+}
+
+#[test]
+fn conflicts_are_counted() {
+    let map = get_map();
+    let tasks = vec![
+        AgentTask::new(0, (0, 0), (1, 0)),
+        AgentTask::new(1, (1, 0), (0, 0)),
+        AgentTask::new(2, (2, 0), (1, 0)),
+        AgentTask::new(3, (0, 1), (1, 2)),
+        AgentTask::new(4, (1, 1), (0, 2))
+    ];
+
+    // The first two agents swap while the last two cross diagonally, then the first and the third meet on (1, 0)
+    let solution = Solution {
+        paths: vec![
+            vec![(0, 0), (1, 0)],
+            vec![(1, 0), (0, 0)],
+            vec![(2, 0), (2, 0), (1, 0)],
+            vec![(0, 1), (1, 2)],
+            vec![(1, 1), (0, 2)]
+        ],
+        ..Default::default()
+    };
+
+    let metrics = Metrics::new("test", &map, &tasks, &solution);
+    assert_eq!(metrics.edge_conflicts, 2);
+    assert_eq!(metrics.vertex_conflicts, 1);
+
+    // The crossing only counts when the solver forbids it
+    let metrics = Metrics::new("test", &map, &tasks, &Solution { diagonal_crossing: DiagonalCrossing::Allow, ..solution.clone() });
+    assert_eq!(metrics.edge_conflicts, 1);
+
+    // Vanished agents don't conflict with anyone
+    let metrics = Metrics::new("test", &map, &tasks, &Solution { agents_vanish: true, ..solution });
+    assert_eq!(metrics.vertex_conflicts, 0);
+}
+
+#[test]
+fn metrics_are_written_as_json_and_csv() {
+    let (tasks, solution) = get_solution();
+    let metrics = Metrics::new("whca", &get_map(), &tasks, &solution);
+
+    assert_eq!(
+        metrics.to_json(),
+        "{\"solver\":\"whca\",\"agents\":3,\"solved_agents\":2,\"sum_of_costs\":8,\"weighted_cost\":1042,\"makespan\":4,\
+\"wait_actions\":3,\"vertex_conflicts\":0,\"edge_conflicts\":0,\"nodes_expanded\":12,\
+\"nodes_expanded_per_agent\":[5,2,5],\"planning_time_us\":1500,\"tick_times_us\":[100,300]}"
+    );
+
+    let header = Metrics::get_csv_header().split(',').collect::<Vec<&str>>();
+    let row = metrics.to_csv();
+    let row = row.split(',').collect::<Vec<&str>>();
+
+    assert_eq!(header, vec![
+        "solver", "agents", "solved_agents", "sum_of_costs", "weighted_cost", "makespan", "wait_actions", "vertex_conflicts", "edge_conflicts",
+        "nodes_expanded", "nodes_expanded_per_agent", "planning_time_us", "ticks", "mean_tick_time_us", "max_tick_time_us"
+    ]);
+    assert_eq!(row, vec!["whca", "3", "2", "8", "1042", "4", "3", "0", "0", "12", "5;2;5", "1500", "2", "200", "300"]);
+}
@@ -0,0 +1,92 @@
+use pathfinding_core::movingai::{parse_map, parse_scenario, LoadError};
+use pathfinding_core::{AgentTask, Terrain};
+
+fn get_error_line(error: LoadError) -> usize {
+    match error {
+        LoadError::Parse { line, .. } => line,
+        LoadError::Io(error) => panic!("unexpected io error {}", error)
+    }
+}
+
+#[test]
+fn maps_are_parsed() {
+    let map = parse_map("type octile\nheight 2\nwidth 3\nmap\n.@W\nTG.\n").unwrap();
+
+    assert_eq!((map.width, map.height), (3, 2));
+    assert_eq!(map.get_terrain((0, 0)), Terrain::Ground(1));
+    assert_eq!(map.get_terrain((1, 0)), Terrain::Wall);
+    assert_eq!(map.get_terrain((2, 0)), Terrain::Water);
+    assert_eq!(map.get_terrain((0, 1)), Terrain::Wall);
+    assert_eq!(map.get_terrain((1, 1)), Terrain::Ground(1));
+}
+
+#[test]
+fn malformed_headers_are_rejected() {
+
+    // Not a number
+    assert_eq!(get_error_line(parse_map("type octile\nheight two\nwidth 3\nmap\n...\n...\n").unwrap_err()), 2);
+
+    // Unknown line
+    assert_eq!(get_error_line(parse_map("type octile\nheight 2\ndepth 3\nmap\n...\n...\n").unwrap_err()), 3);
+
+    // Missing width
+    assert!(parse_map("type octile\nheight 2\nmap\n...\n...\n").is_err());
+
+    // More tiles than a u32 can count
+    let error = parse_map("type octile\nheight 65536\nwidth 65536\nmap\n...\n").unwrap_err();
+    assert!(error.to_string().contains("too large"), "{}", error);
+    assert_eq!(get_error_line(error), 4);
+}
+
+#[test]
+fn malformed_rows_are_rejected() {
+
+    // Unknown terrain letter
+    let error = parse_map("type octile\nheight 2\nwidth 3\nmap\n...\n.X.\n").unwrap_err();
+    assert!(error.to_string().contains("unknown terrain 'X'"), "{}", error);
+    assert_eq!(get_error_line(error), 6);
+
+    // Row too short
+    assert_eq!(get_error_line(parse_map("type octile\nheight 2\nwidth 3\nmap\n..\n...\n").unwrap_err()), 5);
+
+    // Missing row
+    assert!(parse_map("type octile\nheight 2\nwidth 3\nmap\n...\n").is_err());
+}
+
+#[test]
+fn scenarios_are_parsed() {
+    let content = "version 1\n\
+        0\tarena 2.map\t4\t3\t0\t1\t3\t2\t3.41421356\n\
+        \n\
+        3 arena.map 4 3 2 0 1 1 1.41421356\n";
+
+    let tasks = parse_scenario(content).unwrap();
+
+    // The version line and the buckets are skipped, the map name may hold spaces when tab separated
+    assert_eq!(tasks, vec![
+        AgentTask::new(0, (0, 1), (3, 2)),
+        AgentTask::new(1, (2, 0), (1, 1))
+    ]);
+}
+
+#[test]
+fn malformed_scenarios_are_rejected() {
+
+    // Missing optimal length and goal y
+    assert_eq!(get_error_line(parse_scenario("version 1\n0 arena.map 4 3 0 1 3\n").unwrap_err()), 2);
+
+    // Not a coordinate
+    assert_eq!(get_error_line(parse_scenario("version 1\n0 arena.map 4 3 0 -1 3 2 3.4\n").unwrap_err()), 2);
+}
+
+#[test]
+fn out_of_bounds_scenarios_are_rejected() {
+
+    // Start x past the width
+    let error = parse_scenario("version 1\n0 arena.map 4 3 0 1 3 2 3.4\n0 arena.map 4 3 4 1 3 2 3.4\n").unwrap_err();
+    assert!(error.to_string().contains("out of the 4x3 map"), "{}", error);
+    assert_eq!(get_error_line(error), 3);
+
+    // Goal y past the height
+    assert_eq!(get_error_line(parse_scenario("version 1\n0 arena.map 4 3 0 1 3 3 3.4\n").unwrap_err()), 2);
+}
@@ -0,0 +1,253 @@
+use std::error::Error;
+
+use pathfinding_core::movingai::{load_map, load_scenario};
+use pathfinding_core::{AgentTask, GridMap, Metrics, Solution};
+
+use cooperative_pathfinding::{GoalParking, PlannerConfig, PriorityStrategy, TieBreaking};
+
+use crate::{get_demo_map, get_demo_tasks, get_solver};
+
+// Exit statuses of the run command
+pub const EXIT_SUCCESS: i32 = 0;
+pub const EXIT_UNSOLVED: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+
+const USAGE: &str = "usage: cooperative-pathfinding run --solver <astar|whca|cbs> [--map <file.map> --scen <file.scen>] [--agents <count>] [--format <text|json|csv>]
+    [--window <steps>] [--replan <steps>] [--tie-breaking <none|highest-g|lowest-g>]
+    [--priority <fixed|random|farthest|delayed>] [--seed <seed>] [--stall-windows <windows>] [--threads <count>]
+    [--parking <stay|vanish|yield>] [--deadlock-waits <steps>]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+enum OutputFormat {
+    // One line per agent followed by a summary
+    #[default]
+    Text,
+
+    // The metrics of the run as one JSON object
+    Json,
+
+    // A CSV header and the metrics of the run as one row
+    Csv
+}
+
+#[derive(Debug, Default)]
+struct RunOptions {
+    solver: String,
+    map: Option<String>,
+    scen: Option<String>,
+
+    // Only the first agents of the scenario are planned
+    agents: Option<usize>,
+
+    format: OutputFormat,
+
+    // Seeds the random priority, the only one it applies to
+    seed: Option<u64>,
+
+    // Settings of WHCA*
+    config: PlannerConfig,
+    threads: usize
+}
+
+fn parse_options(args: &[String]) -> Result<RunOptions, String> {
+
+    let mut options = RunOptions::default();
+    let mut replan_interval = None;
+    let mut args = args.iter();
+
+    while let Some(flag) = args.next() {
+        let mut value = || args.next().cloned().ok_or(format!("missing value for {}", flag));
+
+        match flag.as_str() {
+            "--solver" => options.solver = value()?,
+            "--map" => options.map = Some(value()?),
+            "--scen" => options.scen = Some(value()?),
+            "--agents" => {
+                let count = value()?;
+                options.agents = Some(count.parse().map_err(|_| format!("invalid agent count {}", count))?);
+            }
+            "--window" => options.config.window_size = parse_steps(flag, &value()?)?,
+            "--replan" => replan_interval = Some(parse_steps(flag, &value()?)?),
+            "--tie-breaking" => {
+                options.config.tie_breaking = match value()?.as_str() {
+                    "none" => TieBreaking::None,
+                    "highest-g" => TieBreaking::HighestG,
+                    "lowest-g" => TieBreaking::LowestG,
+                    tie_breaking => return Err(format!("unknown tie-breaking {}", tie_breaking))
+                };
+            }
+            "--priority" => {
+                options.config.priority = match value()?.as_str() {
+                    "fixed" => PriorityStrategy::Fixed,
+                    "random" => PriorityStrategy::Random { seed: 0 },
+                    "farthest" => PriorityStrategy::FarthestFirst,
+                    "delayed" => PriorityStrategy::MostDelayedFirst,
+                    priority => return Err(format!("unknown priority {}", priority))
+                };
+            }
+            "--seed" => {
+                let seed = value()?;
+                options.seed = Some(seed.parse().map_err(|_| format!("invalid seed {}", seed))?);
+            }
+            "--stall-windows" => {
+                let windows = value()?;
+                options.config.stall_windows = windows.parse().map_err(|_| format!("invalid window count {}", windows))?;
+            }
+            "--deadlock-waits" => {
+                let waits = value()?;
+                options.config.deadlock_waits = waits.parse().map_err(|_| format!("invalid step count {}", waits))?;
+            }
+            "--parking" => {
+                options.config.goal_parking = match value()?.as_str() {
+                    "stay" => GoalParking::Stay,
+                    "vanish" => GoalParking::Vanish,
+                    "yield" => GoalParking::Yield,
+                    parking => return Err(format!("unknown parking {}", parking))
+                };
+            }
+            "--threads" => {
+                let threads = value()?;
+                options.threads = threads.parse().map_err(|_| format!("invalid thread count {}", threads))?;
+            }
+            "--format" => {
+                options.format = match value()?.as_str() {
+                    "text" => OutputFormat::Text,
+                    "json" => OutputFormat::Json,
+                    "csv" => OutputFormat::Csv,
+                    format => return Err(format!("unknown format {}", format))
+                };
+            }
+            _ => return Err(format!("unknown argument {}", flag))
+        }
+    }
+
+    if options.solver.is_empty() {
+        return Err("missing --solver".into());
+    }
+
+    if options.map.is_some() != options.scen.is_some() {
+        return Err("--map and --scen go together".into());
+    }
+
+    match (&mut options.config.priority, options.seed) {
+        (PriorityStrategy::Random { seed }, Some(value)) => *seed = value,
+        (PriorityStrategy::Random { .. }, None) => {}
+        (_, Some(_)) => return Err("--seed only applies to --priority random".into()),
+        (_, None) => {}
+    }
+
+    // Without --replan, agents replan every half window as by default
+    match replan_interval {
+        Some(replan_interval) if replan_interval > options.config.window_size => {
+            return Err("--replan can't be longer than --window".into());
+        }
+        Some(replan_interval) => options.config.replan_interval = replan_interval,
+        None => options.config.replan_interval = (options.config.window_size / 2).max(1)
+    }
+
+    Ok(options)
+}
+
+fn parse_steps(flag: &str, value: &str) -> Result<u32, String> {
+    match value.parse::<u32>() {
+        Ok(steps) if steps > 0 => Ok(steps),
+        _ => Err(format!("invalid step count {} for {}", value, flag))
+    }
+}
+
+/* The demo map and agents are used when no scenario is given */
+fn load_problem(options: &RunOptions) -> Result<(GridMap, Vec<AgentTask>), Box<dyn Error>> {
+
+    let (map, mut tasks) = match (&options.map, &options.scen) {
+        (Some(map), Some(scen)) => (
+            load_map(map).map_err(|error| format!("{}: {}", map, error))?,
+            load_scenario(scen).map_err(|error| format!("{}: {}", scen, error))?
+        ),
+        _ => (get_demo_map(), get_demo_tasks())
+    };
+
+    if let Some(count) = options.agents {
+        tasks.truncate(count);
+    }
+
+    Ok((map, tasks))
+}
+
+fn print_results(tasks: &[AgentTask], solution: &Solution) {
+
+    for (task, path) in tasks.iter().zip(solution.paths.iter()) {
+        let reached = path.last() == Some(&task.goal);
+
+        println!(
+            "agent {}: {:?} -> {:?} {} after {} steps",
+            task.id,
+            task.start,
+            task.goal,
+            if reached { "reached" } else { "stopped" },
+            path.len().saturating_sub(1)
+        );
+    }
+
+    for event in solution.stats.events.iter() {
+        println!("{}", event);
+    }
+
+    println!(
+        "{} agents, makespan {}, {} nodes expanded in {:?}",
+        tasks.len(),
+        solution.get_makespan(),
+        solution.stats.nodes_expanded,
+        solution.stats.planning_time
+    );
+}
+
+/* Will plan the whole problem with no window and return the exit status,
+EXIT_UNSOLVED when the solver fails or leaves an agent out of its goal */
+pub fn run(args: &[String]) -> i32 {
+
+    let options = match parse_options(args) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n{}", message, USAGE);
+            return EXIT_USAGE;
+        }
+    };
+
+    let mut solver = match get_solver(&options.solver, &options.config, options.threads) {
+        Some(solver) => solver,
+        None => {
+            eprintln!("unknown solver {}\n{}", options.solver, USAGE);
+            return EXIT_USAGE;
+        }
+    };
+
+    let (map, tasks) = match load_problem(&options) {
+        Ok(problem) => problem,
+        Err(error) => {
+            eprintln!("{}", error);
+            return EXIT_USAGE;
+        }
+    };
+
+    match solver.solve(&map, &tasks) {
+        Ok(solution) => {
+            let metrics = Metrics::new(solver.name(), &map, &tasks, &solution);
+
+            match options.format {
+                OutputFormat::Text => print_results(&tasks, &solution),
+                OutputFormat::Json => println!("{}", metrics.to_json()),
+                OutputFormat::Csv => println!("{}\n{}", Metrics::get_csv_header(), metrics.to_csv())
+            }
+
+            if metrics.solved_agents == tasks.len() {
+                EXIT_SUCCESS
+            } else {
+                EXIT_UNSOLVED
+            }
+        }
+        Err(error) => {
+            eprintln!("{} failed: {}", solver.name(), error);
+            EXIT_UNSOLVED
+        }
+    }
+}
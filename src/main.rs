@@ -1,16 +1,16 @@
 bracket_terminal::add_wasm_support!();
 
-use std::cell::RefCell;
-use std::collections::HashMap;
-use std::rc::Rc;
 use std::env;
+use std::process;
 
 use bracket_pathfinding::prelude::*;
-use bracket_random::prelude::*;
 use bracket_terminal::prelude::*;
 
-use cooperative_pathfinding::{Agent as Coop_A_Star_Agent, Agents as Coop_A_Star_Agents, Node, WINDOW_SIZE, WorldMap};
-use a_star_pathfinding::{Agent as A_Star_Agent, Agents as A_Star_Agents, AStarMap};
+use cooperative_pathfinding::{ConflictBasedSearch, PlannerConfig, WhcaSolver};
+use a_star_pathfinding::AStarSolver;
+use pathfinding_core::{AgentTask, GridMap, MapfSolver, Solution, Terrain, TerrainLayer, WATER_COST};
+
+mod headless;
 
 static PATHFINDING_MAP_DATA: [u32; 1600] = [
     u32::MAX, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,  u32::MAX, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, u32::MAX, 1, 1,
@@ -34,9 +34,9 @@ static PATHFINDING_MAP_DATA: [u32; 1600] = [
     1, u32::MAX, 1, 1, 1, 1, 1, 1, 1, 1, 1, u32::MAX, u32::MAX, 1, 1, 1, 1, u32::MAX, 1, 1, 1, 1, 1, 1, u32::MAX, u32::MAX, 1, 1, 1, 1, 1, 1, 1, u32::MAX, 1, 1, 1, u32::MAX, 1, 1,
     1, u32::MAX, 1, 1, 1, 1, 1, 1, 1, 1, 1, u32::MAX, u32::MAX, u32::MAX, 1, 1, 1, 1, 1, 1, u32::MAX, 1, 1, 1, 1, 1, 1, u32::MAX, u32::MAX, 1, 1, 1, 1, 1, 1, 1, 1, u32::MAX, 1, 1,
     u32::MAX, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,  u32::MAX, 1, 1, 1, u32::MAX, u32::MAX, 1, 1, 1, 1, 1, 1, 1, 1, u32::MAX, u32::MAX, 1, u32::MAX, 1, 1,
-    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,u32::MAX-1, u32::MAX-1, u32::MAX-1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, u32::MAX, 1, 1,
-    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, u32::MAX-1, u32::MAX-1, u32::MAX-1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, u32::MAX, u32::MAX, 1, 1,
-    1, 1, 1, 1, 1, 1, 1, 1, u32::MAX, 1, 1, 1, 1, 1, 1, 1, 1, u32::MAX-1, u32::MAX-1, u32::MAX-1,  u32::MAX, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, u32::MAX, 1, 1, 1, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,WATER_COST, WATER_COST, WATER_COST, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, u32::MAX, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, WATER_COST, WATER_COST, WATER_COST, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, u32::MAX, u32::MAX, 1, 1,
+    1, 1, 1, 1, 1, 1, 1, 1, u32::MAX, 1, 1, 1, 1, 1, 1, 1, 1, WATER_COST, WATER_COST, WATER_COST,  u32::MAX, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, u32::MAX, 1, 1, 1, 1, 1,
     1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,  u32::MAX, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, u32::MAX, 1, 1,
     1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,  u32::MAX, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, u32::MAX, 1, 1,
     1, u32::MAX, 1, 1, 1, 1, 1, 1, 1, 1,  1, 1, 1, 1, 1, 1, 1, 1, 1, 1,  u32::MAX, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, u32::MAX, 1, 1,
@@ -59,31 +59,12 @@ const WIDTH: i32 = 40;
 const HEIGHT: i32 = 40;
 
 
-#[derive(PartialEq, Copy, Clone)]
-enum TileType {
-    Wall,
-    Floor,
-    Water
-}
-
-#[derive(PartialEq, Copy, Clone)]
-enum Mode {
-    Waiting,
-    Moving,
-}
-#[derive(Default)]
-struct State_Coop_A_Star {
-    map: Vec<TileType>,
-    agents: Coop_A_Star_Agents,
-    world_map: WorldMap,
-    steps: u32
-}
-
-#[derive(Default)]
-struct State_A_Star {
-    map: Vec<TileType>,
-    agents: A_Star_Agents,
-    world_map: AStarMap,
+struct State {
+    map: TerrainLayer,
+    names: Vec<String>,
+    tasks: Vec<AgentTask>,
+    solution: Solution,
+    steps: usize
 }
 
 pub fn xy_idx(x: i32, y: i32) -> usize {
@@ -93,54 +74,49 @@ pub fn xy_idx(x: i32, y: i32) -> usize {
 pub fn idx_xy(idx: usize) -> (i32, i32) {
     (idx as i32 % WIDTH, idx as i32 / WIDTH )
 }
-impl State_Coop_A_Star {
-    pub fn new(world_map: WorldMap, agents: Coop_A_Star_Agents) -> State_Coop_A_Star {
-        let mut state = State_Coop_A_Star {
-            map: vec![TileType::Floor; 40 * 40],
-            world_map,
-            steps: 0,
-            agents,
-            ..Default::default()
-        };
 
-        for i in 0..1600 {
-            if PATHFINDING_MAP_DATA[i] == u32::MAX {
-                state.map[i] = TileType::Wall;
-            }
-            if PATHFINDING_MAP_DATA[i] == u32::MAX - 1 {
-                state.map[i] = TileType::Water;
-            }
+impl State {
+    // The tiles are drawn from the map the agents were planned on
+    pub fn new(map: &GridMap, names: Vec<String>, tasks: Vec<AgentTask>, solution: Solution) -> State {
+        State {
+            map: TerrainLayer::from(map),
+            names,
+            tasks,
+            solution,
+            steps: 0
         }
-        state
     }
 }
 
-impl State_A_Star {
+pub fn get_demo_map() -> GridMap {
+    GridMap::new(Vec::from(PATHFINDING_MAP_DATA), WIDTH as u32, HEIGHT as u32)
+}
 
-    pub fn new(world_map: AStarMap, agents: A_Star_Agents) -> State_A_Star {
-        let mut state = State_A_Star {
-            map: vec![TileType::Floor; 40 * 40],
-            world_map,
-            agents,
-            // ..Default::default()
-        };
+pub fn get_demo_tasks() -> Vec<AgentTask> {
+    vec![
+        AgentTask::new(1, (36, 20), (38, 22)),
+        AgentTask::new(2, (35, 19), (38, 23)),
+        AgentTask::new(3, (33, 20), (38, 24)),
+    ]
+}
 
-        for i in 0..1600 {
-            if PATHFINDING_MAP_DATA[i] == u32::MAX {
-                state.map[i] = TileType::Wall;
-            }
-            if PATHFINDING_MAP_DATA[i] == u32::MAX - 1 {
-                state.map[i] = TileType::Water;
-            }
+// Pick a solver from its command line name, the config and the threads only apply to WHCA*
+fn get_solver(name: &str, config: &PlannerConfig, threads: usize) -> Option<Box<dyn MapfSolver>> {
+    match name {
+        "astar" => Some(Box::new(AStarSolver::new())),
+        "whca" | "hca_star" => {
+            let mut solver = WhcaSolver::new();
+            solver.config = *config;
+            solver.threads = threads;
+            Some(Box::new(solver))
         }
-        state
+        "cbs" => Some(Box::new(ConflictBasedSearch::new())),
+        _ => None
     }
 }
 
-
 // Implement the game loop
-impl GameState for State_Coop_A_Star {
-    #[allow(non_snake_case)]
+impl GameState for State {
     fn tick(&mut self, ctx: &mut BTerm) {
 
         // We'll use batched drawing
@@ -150,23 +126,8 @@ impl GameState for State_Coop_A_Star {
         match ctx.key {
             None => {}
             Some(key) => {
-                if key == VirtualKeyCode::Return && self.steps < WINDOW_SIZE {
-
-                    for i in 1..self.agents.len() + 1 {
-
-                        let mut rc = &self.agents.get(&(i as u32)).unwrap();
-                        let mut agent = &mut *rc.borrow_mut();
-                        // println!("agent {:?}", agent.name);
-
-                        if self.steps % WINDOW_SIZE == 0 && self.steps < WINDOW_SIZE{
-                            agent.get_true_distance_heuristic(&self.world_map, agent.get_start(), agent.get_goal(), &self.agents);
-                            // println!("agent {:?} heuristic",  agent.name);
-                            // agent.print_heuristic(&self.world_map);
-                            agent.set_portion_path(&mut self.world_map, &self.agents);
-                        }
-
-                        agent.current_node = agent.portion_path.pop().unwrap();
-                    }
+                // Each step replays one timestep of the solution
+                if key == VirtualKeyCode::Return && self.steps < self.solution.get_makespan() {
                     self.steps += 1;
                 }
                 if key == VirtualKeyCode::Q {
@@ -178,20 +139,20 @@ impl GameState for State_Coop_A_Star {
         // Iterate the map array, incrementing coordinates as we go.
         let mut y = 0;
         let mut x = 0;
-        for (i, tile) in self.map.iter().enumerate() {
+        for tile in self.map.tiles.iter() {
             // Render a tile depending upon the tile type; now we check visibility as well!
-            let mut fg;
+            let fg;
             let mut glyph = ".";
 
             match tile {
-                TileType::Floor => {
+                Terrain::Ground(_) => {
                     fg = RGB::from_f32(0.5, 0.5, 0.0);
                 }
-                TileType::Wall => {
+                Terrain::Wall => {
                     fg = RGB::from_f32(0.0, 1.0, 0.0);
                     glyph = "#";
                 }
-                TileType::Water => {
+                Terrain::Water => {
                     fg = RGB::from_f32(0.0, 0.8, 1.0);
                     glyph = "0";
                 }
@@ -209,20 +170,23 @@ impl GameState for State_Coop_A_Star {
                 y += 1;
             }
         }
-        for i in 1..self.agents.len() + 1 {
 
-            let mut rc = &self.agents.get(&(i as u32)).unwrap();
-            let mut agent = &mut *rc.borrow_mut();
+        for (i, task) in self.tasks.iter().enumerate() {
 
-            draw_batch.print_color(
-                Point::new(agent.current_node.pos.0, agent.current_node.pos.1),
-                &agent.name,
-                ColorPair::new(RGB::from_f32(1., 0., 0.), RGB::from_f32(0., 0., 0.)),
-            );
+            // Vanished agents only leave their goal on the map
+            if self.solution.is_present(i, self.steps) {
+                let current_node = self.solution.get_position(i, self.steps);
+
+                draw_batch.print_color(
+                    Point::new(current_node.0, current_node.1),
+                    &self.names[i],
+                    ColorPair::new(RGB::from_f32(1., 0., 0.), RGB::from_f32(0., 0., 0.)),
+                );
+            }
 
             draw_batch.print_color(
-                Point::new(agent.goal.pos.0, agent.goal.pos.1),
-                &agent.name,
+                Point::new(task.goal.0, task.goal.1),
+                &self.names[i],
                 ColorPair::new(RGB::from_f32(1., 0., 0.), RGB::from_f32(0., 1., 0.)),
             );
         }
@@ -233,182 +197,35 @@ impl GameState for State_Coop_A_Star {
     }
 }
 
-impl GameState for State_A_Star {
-
-    #[allow(non_snake_case)]
-    fn tick(&mut self, ctx: &mut BTerm) {
+fn main() -> BError {
 
-         // We'll use batched drawing
-         let mut draw_batch = DrawBatch::new();
-         draw_batch.cls();
+    let args: Vec<String> = env::args().collect();
 
-         match ctx.key {
-            None => {}
-            Some(key) => {
-                if key == VirtualKeyCode::Return {
-
-                    for (k, agent) in self.agents.iter_mut() {
-
-                        let mut path = agent.astar_search(&self.world_map);
-                        if path.len() > 0 {                      
-                            println!("agent {} path {:?}", agent.name, path);
-                            agent.current_node = path.pop().unwrap();
-
-                            println!("agent {} path {:?}", agent.name, path);
-
-                            println!("agent {} current_node {:?}", agent.name, agent.current_node );
-                            agent.start_node = match path.last() {
-                                Some(node) => {
-                                    *node
-                                },
-                                None => {
-                                    agent.current_node
-                                }
-                            }                       
-                         }
-                    }
-                }
-                if key == VirtualKeyCode::Q {
-                    return;
-                }
-            }
-        };
+    // Batch runs don't open any window
+    if args.get(1).map(String::as_str) == Some("run") {
+        process::exit(headless::run(&args[2..]));
+    }
 
+    let mut solver = match args.get(1).and_then(|name| get_solver(name, &PlannerConfig::default(), 0)) {
+        Some(solver) => solver,
+        None => return Err("usage: cooperative-pathfinding <astar|whca|cbs> | run --solver <astar|whca|cbs> [...]".into())
+    };
 
-        // Iterate the map array, incrementing coordinates as we go.
-        let mut y = 0;
-        let mut x = 0;
-        for (i, tile) in self.map.iter().enumerate() {
-            // Render a tile depending upon the tile type; now we check visibility as well!
-            let mut fg;
-            let mut glyph = ".";
+    println!("i have choosen {}", solver.name());
 
-            match tile {
-                TileType::Floor => {
-                    fg = RGB::from_f32(0.5, 0.5, 0.0);
-                }
-                TileType::Wall => {
-                    fg = RGB::from_f32(0.0, 1.0, 0.0);
-                    glyph = "#";
-                }
-                TileType::Water => {
-                    fg = RGB::from_f32(0.0, 0.8, 1.0);
-                    glyph = "0";
-                }
-            }
-            draw_batch.print_color(
-                Point::new(x, y),
-                glyph,
-                ColorPair::new(fg, RGB::from_f32(0., 0.,  0.)),
-            );
+    let map = get_demo_map();
 
-            // Move the coordinates
-            x += 1;
-            if x > WIDTH - 1 {
-                x = 0;
-                y += 1;
-            }
-        }
-    
-        for (k, agent) in self.agents.iter() {
+    let names = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+    let tasks = get_demo_tasks();
 
-            draw_batch.print_color(
-                Point::new(agent.current_node.0, agent.current_node.1),
-                &agent.name,
-                ColorPair::new(RGB::from_f32(1., 0., 0.), RGB::from_f32(0., 0., 0.)),
-            );
+    let solution = solver.solve(&map, &tasks)?;
 
-            draw_batch.print_color(
-                Point::new(agent.end_node.0, agent.end_node.1),
-                &agent.name,
-                ColorPair::new(RGB::from_f32(1., 0., 0.), RGB::from_f32(0., 1., 0.)),
-            );
-        }
+    let gs = State::new(&map, names, tasks, solution);
 
-        // Submit the rendering
-        draw_batch.submit(0).expect("Batch error");
-        render_draw_buffer(ctx).expect("Render error");
+    let context = BTermBuilder::simple(WIDTH , HEIGHT).unwrap()
+    .with_title(format!("Pathfinding ({})", solver.name()))
+    .with_dimensions(256, 192)
+    .build()?;
 
-    }
+    main_loop(context, gs)
 }
-
-fn main() -> BError {
-
-    let args: Vec<String> = env::args().collect();
-
-    match args[1].clone().as_str() {
-            "astar" => {
-                println!("i have choosen A*");
-                let map_data = PATHFINDING_MAP_DATA.clone();
-                let a_star_map = AStarMap::new(Vec::from(map_data), WIDTH as u32, HEIGHT as u32);
-
-                let mut agents = A_Star_Agents::new();
-                let agent_1 = A_Star_Agent::new(1, "a".into(), (36, 20), (38, 22));
-                let agent_2 = A_Star_Agent::new(1, "b".into(), (35, 19), (38, 23));
-                let agent_3 = A_Star_Agent::new(1, "c".into(), (33, 20), (38, 24));
-
-                agents.insert(1, agent_1);
-                agents.insert(2, agent_2);
-                agents.insert(3, agent_3);
-
-                let gs = State_A_Star::new(a_star_map, agents);
-
-                let context = BTermBuilder::simple(WIDTH , HEIGHT).unwrap()
-                .with_title("Pathfinding (A*)")
-                .with_dimensions(256, 192)
-                .build()?;
-            
-                main_loop(context, gs)
-            },
-            "hca_star" => {
-                println!("i have choosen Cooperative A*");
-                let map_data = PATHFINDING_MAP_DATA.clone();
-
-                let world_map = WorldMap::new(Vec::from(map_data), WIDTH as u32, HEIGHT as u32);
-        
-                let mut agents = Coop_A_Star_Agents::new();
-        
-                let mut agent_1 = Coop_A_Star_Agent::new(1, "a".into());
-                agent_1.set_start(Node {pos: (36, 20), g_score: 0, f_score: 0 });
-                agent_1.set_goal(Node {pos: (38, 22), g_score: 0, f_score: 0 });
-        
-                let mut agent_2 = Coop_A_Star_Agent::new(2, "b".into());
-                agent_2.set_start(Node {pos: (35, 19), g_score: 0, f_score: 0 });
-                agent_2.set_goal(Node{ pos: (38, 23), g_score: 0, f_score: 0 });
-        
-                let mut agent_3 = Coop_A_Star_Agent::new(3, "c".into());
-                agent_3.set_start(Node {pos: (33, 20), g_score: 0, f_score: 0 });
-                agent_3.set_goal(Node{ pos: (38, 24), g_score: 0, f_score: 0 });
-        
-        
-                // let mut agent_1 = Agent::new(1, "a".into());
-                // agent_1.set_start(Node {pos: (1, 1), g_score: 0, f_score: 0 });
-                // agent_1.set_goal(Node {pos: (1, 8), g_score: 0, f_score: 0 });
-        
-                // let mut agent_2 = Agent::new(2, "b".into());
-                // agent_2.set_start(Node {pos: (1, 5), g_score: 0, f_score: 0 });
-                // agent_2.set_goal(Node{ pos: (1, 1), g_score: u32::MAX, f_score: 0 });
-        
-                // let mut agent_3 = Agent::new(3, "c".into());
-                // agent_3.set_start(Node {pos: (5, 1), g_score: 0, f_score: 0 });
-                // agent_3.set_goal(Node{ pos: (0, 10), g_score: u32::MAX, f_score: 0 });
-        
-        
-                agents.insert(1, Rc::new(RefCell::new(agent_1)));
-                agents.insert(2, Rc::new(RefCell::new(agent_2)));
-                agents.insert(3, Rc::new(RefCell::new(agent_3)));
-        
-                let gs = State_Coop_A_Star::new(world_map, agents);
-
-                let context = BTermBuilder::simple(WIDTH , HEIGHT).unwrap()
-                .with_title("Collaborative Pathfinding (WHCA*)")
-                .with_dimensions(256, 192)
-                .build()?;
-            
-                main_loop(context, gs)
-            },
-            _ => {panic!("bad argument");}
-        }
-    
-
-}
\ No newline at end of file
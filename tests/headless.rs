@@ -0,0 +1,90 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use pathfinding_core::AgentTask;
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_cooperative-pathfinding"))
+        .arg("run")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+/* Will write a scenario to the temporary directory and return the paths of its map and scen files */
+fn write_problem(name: &str, rows: &[&str], tasks: &[AgentTask]) -> (String, String) {
+    let directory = env::temp_dir().join(format!("cooperative-pathfinding-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&directory).unwrap();
+
+    let map_path = directory.join(format!("{}.map", name));
+    let scen_path = directory.join(format!("{}.scen", name));
+    let (width, height) = (rows[0].len(), rows.len());
+
+    fs::write(&map_path, format!("type octile\nheight {}\nwidth {}\nmap\n{}\n", height, width, rows.join("\n"))).unwrap();
+
+    let lines = tasks.iter()
+        .map(|task| format!("0\t{}.map\t{}\t{}\t{}\t{}\t{}\t{}\t0", name, width, height, task.start.0, task.start.1, task.goal.0, task.goal.1))
+        .collect::<Vec<String>>();
+    fs::write(&scen_path, format!("version 1\n{}\n", lines.join("\n"))).unwrap();
+
+    let to_string = |path: PathBuf| path.to_str().unwrap().to_string();
+    (to_string(map_path), to_string(scen_path))
+}
+
+#[test]
+fn solved_runs_succeed() {
+    let (map, scen) = write_problem("solved", &["....", "....", "...."], &[AgentTask::new(0, (0, 0), (3, 2)), AgentTask::new(1, (3, 0), (0, 2))]);
+
+    for solver in ["astar", "whca", "cbs"].iter() {
+        let output = run(&["--solver", solver, "--map", &map, "--scen", &scen, "--format", "csv"]);
+        assert_eq!(output.status.code(), Some(0), "{} {}", solver, String::from_utf8_lossy(&output.stderr));
+        assert_eq!(String::from_utf8_lossy(&output.stdout).lines().count(), 2);
+    }
+
+    // The demo problem is used without a scenario
+    assert_eq!(run(&["--solver", "whca"]).status.code(), Some(0));
+
+    // Without --replan, a short window replans every half window
+    for window in ["1", "4"].iter() {
+        assert_eq!(run(&["--solver", "whca", "--window", window]).status.code(), Some(0), "{}", window);
+    }
+    assert_eq!(run(&["--solver", "whca", "--window", "4", "--replan", "4"]).status.code(), Some(0));
+
+    // A seed goes with the random priority
+    assert_eq!(run(&["--solver", "whca", "--priority", "random", "--seed", "7"]).status.code(), Some(0));
+}
+
+#[test]
+fn bad_options_are_usage_errors() {
+    let usage_errors: [&[&str]; 9] = [
+        &[],
+        &["--solver", "whca", "--window", "4", "--replan", "5"],
+        &["--solver", "dijkstra"],
+        &["--solver", "whca", "--window", "0"],
+        &["--solver", "whca", "--format", "xml"],
+        &["--solver", "whca", "--map", "arena.map"],
+        &["--solver", "whca", "--map", "missing.map", "--scen", "missing.scen"],
+        &["--solver", "whca", "--seed", "7"],
+        &["--solver", "whca", "--priority", "farthest", "--seed", "7"]
+    ];
+
+    for args in usage_errors.iter() {
+        let output = run(args);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+        assert!(!output.stderr.is_empty(), "{:?}", args);
+    }
+}
+
+#[test]
+fn unsolvable_runs_fail() {
+
+    // The goal of the second agent is walled off
+    let (map, scen) = write_problem("unsolvable", &["..@.", "..@."], &[AgentTask::new(0, (0, 0), (1, 1)), AgentTask::new(1, (1, 0), (3, 1))]);
+
+    for solver in ["astar", "whca", "cbs"].iter() {
+        let output = run(&["--solver", solver, "--map", &map, "--scen", &scen]);
+        assert_ne!(output.status.code(), Some(0), "{}", solver);
+        assert_ne!(output.status.code(), Some(2), "{}", solver);
+    }
+}